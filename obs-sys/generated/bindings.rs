@@ -22199,3 +22199,298 @@ impl Default for __va_list_tag {
 pub struct __locale_data {
     pub _address: u8,
 }
+#[repr(C)]
+#[derive(Debug, Default, Copy, Clone)]
+pub struct gif_frame {
+    pub display: bool,
+    pub frame_delay: ::std::os::raw::c_uint,
+    pub frame_pointer: ::std::os::raw::c_uint,
+    pub virgin: bool,
+    pub opaque: bool,
+    pub redraw_required: bool,
+    pub disposal_method: ::std::os::raw::c_uchar,
+    pub transparency: bool,
+    pub transparency_index: ::std::os::raw::c_uchar,
+    pub redraw_x: ::std::os::raw::c_uint,
+    pub redraw_y: ::std::os::raw::c_uint,
+    pub redraw_width: ::std::os::raw::c_uint,
+    pub redraw_height: ::std::os::raw::c_uint,
+}
+pub type gif_bitmap_cb_create = ::std::option::Option<
+    unsafe extern "C" fn(
+        width: ::std::os::raw::c_int,
+        height: ::std::os::raw::c_int,
+    ) -> *mut ::std::os::raw::c_void,
+>;
+pub type gif_bitmap_cb_destroy =
+    ::std::option::Option<unsafe extern "C" fn(bitmap: *mut ::std::os::raw::c_void)>;
+pub type gif_bitmap_cb_get_buffer = ::std::option::Option<
+    unsafe extern "C" fn(bitmap: *mut ::std::os::raw::c_void) -> *mut ::std::os::raw::c_uchar,
+>;
+pub type gif_bitmap_cb_set_opaque =
+    ::std::option::Option<unsafe extern "C" fn(bitmap: *mut ::std::os::raw::c_void, opaque: bool)>;
+pub type gif_bitmap_cb_test_opaque =
+    ::std::option::Option<unsafe extern "C" fn(bitmap: *mut ::std::os::raw::c_void) -> bool>;
+pub type gif_bitmap_cb_modified =
+    ::std::option::Option<unsafe extern "C" fn(bitmap: *mut ::std::os::raw::c_void)>;
+#[repr(C)]
+#[derive(Debug, Default, Copy, Clone)]
+pub struct gif_bitmap_callback_vt {
+    pub bitmap_create: gif_bitmap_cb_create,
+    pub bitmap_destroy: gif_bitmap_cb_destroy,
+    pub bitmap_get_buffer: gif_bitmap_cb_get_buffer,
+    pub bitmap_set_opaque: gif_bitmap_cb_set_opaque,
+    pub bitmap_test_opaque: gif_bitmap_cb_test_opaque,
+    pub bitmap_modified: gif_bitmap_cb_modified,
+}
+pub type gif_result = ::std::os::raw::c_int;
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct gif_animation {
+    pub bitmap_callbacks: gif_bitmap_callback_vt,
+    pub gif_data: *mut ::std::os::raw::c_uchar,
+    pub width: ::std::os::raw::c_uint,
+    pub height: ::std::os::raw::c_uint,
+    pub frame_count: ::std::os::raw::c_uint,
+    pub frame_count_partial: ::std::os::raw::c_uint,
+    pub frames: *mut gif_frame,
+    pub decoded_frame: ::std::os::raw::c_int,
+    pub frame_image: *mut ::std::os::raw::c_void,
+    pub loop_count: ::std::os::raw::c_int,
+    pub current_error: gif_result,
+    pub buffer_position: ::std::os::raw::c_uint,
+    pub buffer_size: ::std::os::raw::c_uint,
+    pub frame_holders: ::std::os::raw::c_uint,
+    pub background_index: ::std::os::raw::c_uint,
+    pub aspect_ratio: ::std::os::raw::c_uint,
+    pub colour_table_size: ::std::os::raw::c_uint,
+    pub global_colours: bool,
+    pub global_colour_table: *mut ::std::os::raw::c_uint,
+    pub local_colour_table: *mut ::std::os::raw::c_uint,
+}
+impl Default for gif_animation {
+    fn default() -> Self {
+        let mut s = ::std::mem::MaybeUninit::<Self>::uninit();
+        unsafe {
+            ::std::ptr::write_bytes(s.as_mut_ptr(), 0, 1);
+            s.assume_init()
+        }
+    }
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct gs_image_file {
+    pub texture: *mut gs_texture_t,
+    pub format: gs_color_format,
+    pub cx: u32,
+    pub cy: u32,
+    pub is_animated_gif: bool,
+    pub frame_updated: bool,
+    pub loaded: bool,
+    pub gif: gif_animation,
+    pub gif_data: *mut u8,
+    pub animation_frame_cache: *mut *mut u8,
+    pub animation_frame_data: *mut u8,
+    pub cur_time: u64,
+    pub cur_frame: ::std::os::raw::c_int,
+    pub cur_loop: ::std::os::raw::c_int,
+    pub last_decoded_frame: ::std::os::raw::c_int,
+    pub texture_data: *mut u8,
+    pub bitmap_callbacks: gif_bitmap_callback_vt,
+}
+impl Default for gs_image_file {
+    fn default() -> Self {
+        let mut s = ::std::mem::MaybeUninit::<Self>::uninit();
+        unsafe {
+            ::std::ptr::write_bytes(s.as_mut_ptr(), 0, 1);
+            s.assume_init()
+        }
+    }
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct gs_image_file2 {
+    pub image: gs_image_file,
+    pub mem_usage: u64,
+}
+impl Default for gs_image_file2 {
+    fn default() -> Self {
+        let mut s = ::std::mem::MaybeUninit::<Self>::uninit();
+        unsafe {
+            ::std::ptr::write_bytes(s.as_mut_ptr(), 0, 1);
+            s.assume_init()
+        }
+    }
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct gs_image_file3 {
+    pub image2: gs_image_file2,
+    pub alpha_mode: gs_image_alpha_mode,
+}
+impl Default for gs_image_file3 {
+    fn default() -> Self {
+        let mut s = ::std::mem::MaybeUninit::<Self>::uninit();
+        unsafe {
+            ::std::ptr::write_bytes(s.as_mut_ptr(), 0, 1);
+            s.assume_init()
+        }
+    }
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct gs_image_file4 {
+    pub image3: gs_image_file3,
+    pub space: gs_color_space,
+}
+impl Default for gs_image_file4 {
+    fn default() -> Self {
+        let mut s = ::std::mem::MaybeUninit::<Self>::uninit();
+        unsafe {
+            ::std::ptr::write_bytes(s.as_mut_ptr(), 0, 1);
+            s.assume_init()
+        }
+    }
+}
+pub type gs_image_file_t = gs_image_file;
+pub type gs_image_file2_t = gs_image_file2;
+pub type gs_image_file3_t = gs_image_file3;
+pub type gs_image_file4_t = gs_image_file4;
+extern "C" {
+    pub fn gs_image_file_init(image: *mut gs_image_file_t, file: *const ::std::os::raw::c_char);
+}
+extern "C" {
+    pub fn gs_image_file_free(image: *mut gs_image_file_t);
+}
+extern "C" {
+    pub fn gs_image_file_init_texture(image: *mut gs_image_file_t);
+}
+extern "C" {
+    pub fn gs_image_file_tick(image: *mut gs_image_file_t, elapsed_time_ns: u64) -> bool;
+}
+extern "C" {
+    pub fn gs_image_file_update_texture(image: *mut gs_image_file_t);
+}
+extern "C" {
+    pub fn gs_image_file2_init(if2: *mut gs_image_file2_t, file: *const ::std::os::raw::c_char);
+}
+extern "C" {
+    pub fn gs_image_file3_init(
+        if3: *mut gs_image_file3_t,
+        file: *const ::std::os::raw::c_char,
+        alpha_mode: gs_image_alpha_mode,
+    );
+}
+extern "C" {
+    pub fn gs_image_file4_init(
+        if4: *mut gs_image_file4_t,
+        file: *const ::std::os::raw::c_char,
+        alpha_mode: gs_image_alpha_mode,
+        space: gs_color_space,
+    );
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct audio_resampler {
+    _unused: [u8; 0],
+}
+pub type audio_resampler_t = audio_resampler;
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct resample_info {
+    pub samples_per_sec: u32,
+    pub format: audio_format,
+    pub speakers: speaker_layout,
+}
+#[test]
+fn bindgen_test_layout_resample_info() {
+    const UNINIT: ::std::mem::MaybeUninit<resample_info> = ::std::mem::MaybeUninit::uninit();
+    let ptr = UNINIT.as_ptr();
+    assert_eq!(
+        ::std::mem::size_of::<resample_info>(),
+        12usize,
+        concat!("Size of: ", stringify!(resample_info))
+    );
+    assert_eq!(
+        ::std::mem::align_of::<resample_info>(),
+        4usize,
+        concat!("Alignment of ", stringify!(resample_info))
+    );
+    assert_eq!(
+        unsafe { ::std::ptr::addr_of!((*ptr).samples_per_sec) as usize - ptr as usize },
+        0usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(resample_info),
+            "::",
+            stringify!(samples_per_sec)
+        )
+    );
+    assert_eq!(
+        unsafe { ::std::ptr::addr_of!((*ptr).format) as usize - ptr as usize },
+        4usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(resample_info),
+            "::",
+            stringify!(format)
+        )
+    );
+    assert_eq!(
+        unsafe { ::std::ptr::addr_of!((*ptr).speakers) as usize - ptr as usize },
+        8usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(resample_info),
+            "::",
+            stringify!(speakers)
+        )
+    );
+}
+impl Default for resample_info {
+    fn default() -> Self {
+        let mut s = ::std::mem::MaybeUninit::<Self>::uninit();
+        unsafe {
+            ::std::ptr::write_bytes(s.as_mut_ptr(), 0, 1);
+            s.assume_init()
+        }
+    }
+}
+extern "C" {
+    pub fn audio_resampler_create(
+        dst: *const resample_info,
+        src: *const resample_info,
+    ) -> *mut audio_resampler_t;
+}
+extern "C" {
+    pub fn audio_resampler_destroy(resampler: *mut audio_resampler_t);
+}
+extern "C" {
+    pub fn audio_resampler_resample(
+        resampler: *mut audio_resampler_t,
+        output: *mut *mut u8,
+        out_frames: *mut u32,
+        ts_offset: *mut u64,
+        input: *const *const u8,
+        in_frames: u32,
+    ) -> bool;
+}
+pub const obs_nix_platform_type_OBS_NIX_PLATFORM_X11_EGL: obs_nix_platform_type = 0;
+pub const obs_nix_platform_type_OBS_NIX_PLATFORM_WAYLAND: obs_nix_platform_type = 1;
+pub type obs_nix_platform_type = ::std::os::raw::c_uint;
+extern "C" {
+    #[doc = " Sets the Unix platform.\n @param platform The platform to select.\n @note This must be set before obs_startup()."]
+    pub fn obs_set_nix_platform(platform: obs_nix_platform_type);
+}
+extern "C" {
+    #[doc = " Gets the host platform."]
+    pub fn obs_get_nix_platform() -> obs_nix_platform_type;
+}
+extern "C" {
+    #[doc = " Sets the host platform's display connection.\n @param display The host display connection."]
+    pub fn obs_set_nix_platform_display(display: *mut ::std::os::raw::c_void);
+}
+extern "C" {
+    #[doc = " Gets the host platform's display connection."]
+    pub fn obs_get_nix_platform_display() -> *mut ::std::os::raw::c_void;
+}
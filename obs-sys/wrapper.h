@@ -1,2 +1,7 @@
 #include <obs-module.h>
 #include <obs-frontend-api.h>
+#include <graphics/image-file.h>
+#include <media-io/audio-resampler.h>
+#if defined(__linux__) || defined(__FreeBSD__) || defined(__DragonFly__)
+#include <obs-nix-platform.h>
+#endif
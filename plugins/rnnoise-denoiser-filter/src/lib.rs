@@ -1,5 +1,7 @@
 use nnnoiseless::DenoiseState;
-use obs_wrapper::{media::audio, obs_register_module, obs_string, prelude::*, source::*};
+use obs_wrapper::{
+    media::audio, obs_register_module, obs_string, obs_sys::MAX_AV_PLANES, prelude::*, source::*,
+};
 
 use std::collections::VecDeque;
 
@@ -73,7 +75,7 @@ impl UpdateSource for RnnoiseDenoiserFilter {
 }
 
 impl FilterAudioSource for RnnoiseDenoiserFilter {
-    fn filter_audio(&mut self, audio: &mut audio::AudioDataContext) {
+    fn filter_audio(&mut self, audio: &mut audio::AudioFrame) {
         let data = self;
         let state = &mut data.state;
         let input_ring_buffer = &mut data.input;
@@ -82,12 +84,16 @@ impl FilterAudioSource for RnnoiseDenoiserFilter {
         let temp = &mut data.temp;
         let temp_out = &mut data.temp_out;
 
-        if let Some(base) = audio.get_channel_as_mut_slice(0) {
-            for channel in 1..data.channels {
-                let buffer = audio
-                    .get_channel_as_mut_slice(channel)
-                    .expect("Channel count said there was a buffer here.");
-
+        // collected without allocating, this runs on the audio thread
+        let mut channels: [Option<&mut [f32]>; MAX_AV_PLANES as usize] = Default::default();
+        for (slot, channel) in channels
+            .iter_mut()
+            .zip(audio.iter_channels_mut().take(data.channels))
+        {
+            *slot = Some(channel);
+        }
+        if let [Some(base), others @ ..] = &mut channels {
+            for buffer in others.iter().flatten() {
                 for (output, input) in base.iter_mut().zip(buffer.iter()) {
                     *output = (*output + *input) / 2.;
                 }
@@ -158,14 +164,8 @@ impl FilterAudioSource for RnnoiseDenoiserFilter {
                 }
             }
 
-            for channel in 1..data.channels {
-                let buffer = audio
-                    .get_channel_as_mut_slice(channel)
-                    .expect("Channel count said there was a buffer here.");
-
-                for (output, input) in buffer.iter_mut().zip(base.iter()) {
-                    *output = *input;
-                }
+            for buffer in others.iter_mut().flatten() {
+                buffer.copy_from_slice(base);
             }
         }
     }
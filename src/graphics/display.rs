@@ -1,10 +1,20 @@
 use obs_sys::{
-    obs_display_add_draw_callback, obs_display_destroy, obs_display_enabled,
-    obs_display_remove_draw_callback, obs_display_resize, obs_display_set_background_color,
-    obs_display_set_enabled, obs_display_size, obs_display_t, obs_render_main_texture,
+    gs_init_data, gs_ortho, gs_projection_pop, gs_projection_push, gs_set_viewport,
+    gs_viewport_pop, gs_viewport_push, obs_display_add_draw_callback, obs_display_create,
+    obs_display_destroy, obs_display_enabled, obs_display_remove_draw_callback, obs_display_resize,
+    obs_display_set_background_color, obs_display_set_enabled, obs_display_size, obs_display_t,
+    obs_render_main_texture, obs_source_get_height, obs_source_get_width, obs_source_video_render,
 };
 
-use super::GraphicsColorFormat;
+use obs_sys::{obs_data_item_get_int, obs_data_item_t, obs_data_set_default_int, obs_data_t};
+
+use super::{GraphicsColorFormat, Vec4};
+use crate::{
+    data::{DataType, FromDataItem},
+    source::SourceRef,
+    string::ObsString,
+    wrapper::PtrWrapper,
+};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Color {
@@ -39,14 +49,53 @@ impl Color {
         Color { r, g, b, a }
     }
 
+    /// Pack the color into a single pixel of `format`, in memory order.
+    ///
+    /// # Panics
+    /// If a pixel of `format` doesn't fit in a `u32` or `format` is block
+    /// compressed or unknown.
+    #[deprecated(note = "use `to_bytes`, which doesn't panic on other formats")]
     pub fn as_format(self, format: GraphicsColorFormat) -> u32 {
         match format {
             GraphicsColorFormat::RGBA => self.as_rgba(),
             GraphicsColorFormat::BGRA => self.as_bgra(),
-            _ => unimplemented!("unsupported color format"),
+            _ => {
+                let bytes = self
+                    .to_bytes(format)
+                    .filter(|bytes| bytes.len() <= 4)
+                    .expect("unsupported color format");
+                let mut packed = [0; 4];
+                packed[..bytes.len()].copy_from_slice(&bytes);
+                u32::from_ne_bytes(packed)
+            }
         }
     }
 
+    /// Pack the color into a single pixel of `format`. Returns `None` for
+    /// block compressed and unknown formats.
+    pub fn to_bytes(self, format: GraphicsColorFormat) -> Option<Vec<u8>> {
+        ColorF32::from(self).to_bytes(format)
+    }
+
+    /// Unpack a single pixel of `format`.
+    pub fn from_bytes(format: GraphicsColorFormat, bytes: &[u8]) -> Option<Self> {
+        ColorF32::from_bytes(format, bytes).map(Color::from)
+    }
+
+    /// Convert from the integer stored in [`DataObj`](crate::data::DataObj)
+    /// by a [`ColorProp`](crate::properties::ColorProp), which is laid out
+    /// as `0xAABBGGRR`.
+    pub fn from_prop(value: i64) -> Self {
+        let [r, g, b, a] = (value as u32).to_le_bytes();
+        Color { r, g, b, a }
+    }
+
+    /// Convert to the integer stored by a
+    /// [`ColorProp`](crate::properties::ColorProp).
+    pub fn as_prop(self) -> i64 {
+        u32::from_le_bytes([self.r, self.g, self.b, self.a]) as i64
+    }
+
     pub fn as_rgba(self) -> u32 {
         u32::from_ne_bytes([self.r, self.g, self.b, self.a])
     }
@@ -81,6 +130,276 @@ impl Color {
     }
 }
 
+impl FromDataItem for Color {
+    fn typ() -> DataType {
+        DataType::Int
+    }
+    unsafe fn from_item_unchecked(item: *mut obs_data_item_t) -> Option<Self> {
+        Some(Color::from_prop(obs_data_item_get_int(item)))
+    }
+    unsafe fn set_default_unchecked(obj: *mut obs_data_t, name: ObsString, val: Self) {
+        obs_data_set_default_int(obj, name.as_ptr(), val.as_prop())
+    }
+}
+
+/// A color with floating point channels, usually in the `0.0..=1.0` range
+/// but allowed to exceed it for HDR content.
+///
+/// Whether the channels are sRGB encoded or linear depends on where the color
+/// came from, see [`srgb_to_linear`](Self::srgb_to_linear) and
+/// [`linear_to_srgb`](Self::linear_to_srgb).
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default)]
+pub struct ColorF32 {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
+impl ColorF32 {
+    pub const fn new(r: f32, g: f32, b: f32, a: f32) -> Self {
+        ColorF32 { r, g, b, a }
+    }
+
+    /// Decode sRGB encoded color channels to linear, leaving alpha as is.
+    pub fn srgb_to_linear(self) -> Self {
+        ColorF32 {
+            r: srgb_nonlinear_to_linear(self.r),
+            g: srgb_nonlinear_to_linear(self.g),
+            b: srgb_nonlinear_to_linear(self.b),
+            a: self.a,
+        }
+    }
+
+    /// Encode linear color channels as sRGB, leaving alpha as is.
+    pub fn linear_to_srgb(self) -> Self {
+        ColorF32 {
+            r: srgb_linear_to_nonlinear(self.r),
+            g: srgb_linear_to_nonlinear(self.g),
+            b: srgb_linear_to_nonlinear(self.b),
+            a: self.a,
+        }
+    }
+
+    /// Multiply the color channels by alpha.
+    pub fn premultiply(self) -> Self {
+        ColorF32 {
+            r: self.r * self.a,
+            g: self.g * self.a,
+            b: self.b * self.a,
+            a: self.a,
+        }
+    }
+
+    /// The color as a shader `float4`.
+    pub fn as_vec4(self) -> Vec4 {
+        Vec4::new(self.r, self.g, self.b, self.a)
+    }
+
+    pub fn from_vec4(v: &Vec4) -> Self {
+        ColorF32::new(v.x(), v.y(), v.z(), v.w())
+    }
+
+    /// Pack the color into a single pixel of `format`. Returns `None` for
+    /// block compressed and unknown formats.
+    pub fn to_bytes(self, format: GraphicsColorFormat) -> Option<Vec<u8>> {
+        let Self { r, g, b, a } = self;
+        let bytes = match format {
+            GraphicsColorFormat::A8 => vec![unorm8(a)],
+            GraphicsColorFormat::R8 => vec![unorm8(r)],
+            GraphicsColorFormat::R8G8 => vec![unorm8(r), unorm8(g)],
+            GraphicsColorFormat::RGBA => vec![unorm8(r), unorm8(g), unorm8(b), unorm8(a)],
+            GraphicsColorFormat::BGRA => vec![unorm8(b), unorm8(g), unorm8(r), unorm8(a)],
+            GraphicsColorFormat::BGRX => vec![unorm8(b), unorm8(g), unorm8(r), 255],
+            GraphicsColorFormat::R10G10B10A2 => {
+                let packed =
+                    unorm(r, 10) | unorm(g, 10) << 10 | unorm(b, 10) << 20 | unorm(a, 2) << 30;
+                packed.to_le_bytes().to_vec()
+            }
+            GraphicsColorFormat::R16 => (unorm(r, 16) as u16).to_le_bytes().to_vec(),
+            GraphicsColorFormat::RGBA16 => [r, g, b, a]
+                .iter()
+                .flat_map(|&c| (unorm(c, 16) as u16).to_le_bytes())
+                .collect(),
+            GraphicsColorFormat::R16F => f32_to_f16(r).to_le_bytes().to_vec(),
+            GraphicsColorFormat::RG16F => [r, g]
+                .iter()
+                .flat_map(|&c| f32_to_f16(c).to_le_bytes())
+                .collect(),
+            GraphicsColorFormat::RGBA16F => [r, g, b, a]
+                .iter()
+                .flat_map(|&c| f32_to_f16(c).to_le_bytes())
+                .collect(),
+            GraphicsColorFormat::R32F => r.to_le_bytes().to_vec(),
+            GraphicsColorFormat::RG32F => [r, g].iter().flat_map(|c| c.to_le_bytes()).collect(),
+            GraphicsColorFormat::RGBA32F => {
+                [r, g, b, a].iter().flat_map(|c| c.to_le_bytes()).collect()
+            }
+            GraphicsColorFormat::DXT1
+            | GraphicsColorFormat::DXT3
+            | GraphicsColorFormat::DXT5
+            | GraphicsColorFormat::UNKNOWN => return None,
+        };
+        Some(bytes)
+    }
+
+    /// Unpack a single pixel of `format`. Channels missing from the format are
+    /// zero, except alpha which is one. Returns `None` if `bytes` is too short
+    /// or the format is block compressed or unknown.
+    pub fn from_bytes(format: GraphicsColorFormat, bytes: &[u8]) -> Option<Self> {
+        if bytes.len() < format.bytes_per_pixel()? {
+            return None;
+        }
+        let u8_at = |i: usize| bytes[i] as f32 / 255.;
+        let u16_at = |i: usize| u16::from_le_bytes([bytes[i * 2], bytes[i * 2 + 1]]);
+        let f32_at = |i: usize| {
+            f32::from_le_bytes([
+                bytes[i * 4],
+                bytes[i * 4 + 1],
+                bytes[i * 4 + 2],
+                bytes[i * 4 + 3],
+            ])
+        };
+        let unorm16_at = |i: usize| u16_at(i) as f32 / 65535.;
+        let half_at = |i: usize| f16_to_f32(u16_at(i));
+
+        let color = match format {
+            GraphicsColorFormat::A8 => ColorF32::new(0., 0., 0., u8_at(0)),
+            GraphicsColorFormat::R8 => ColorF32::new(u8_at(0), 0., 0., 1.),
+            GraphicsColorFormat::R8G8 => ColorF32::new(u8_at(0), u8_at(1), 0., 1.),
+            GraphicsColorFormat::RGBA => ColorF32::new(u8_at(0), u8_at(1), u8_at(2), u8_at(3)),
+            GraphicsColorFormat::BGRA => ColorF32::new(u8_at(2), u8_at(1), u8_at(0), u8_at(3)),
+            GraphicsColorFormat::BGRX => ColorF32::new(u8_at(2), u8_at(1), u8_at(0), 1.),
+            GraphicsColorFormat::R10G10B10A2 => {
+                let packed = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+                let channel = |shift: u32, bits: u32| {
+                    let max = (1 << bits) - 1;
+                    ((packed >> shift) & max) as f32 / max as f32
+                };
+                ColorF32::new(
+                    channel(0, 10),
+                    channel(10, 10),
+                    channel(20, 10),
+                    channel(30, 2),
+                )
+            }
+            GraphicsColorFormat::R16 => ColorF32::new(unorm16_at(0), 0., 0., 1.),
+            GraphicsColorFormat::RGBA16 => {
+                ColorF32::new(unorm16_at(0), unorm16_at(1), unorm16_at(2), unorm16_at(3))
+            }
+            GraphicsColorFormat::R16F => ColorF32::new(half_at(0), 0., 0., 1.),
+            GraphicsColorFormat::RG16F => ColorF32::new(half_at(0), half_at(1), 0., 1.),
+            GraphicsColorFormat::RGBA16F => {
+                ColorF32::new(half_at(0), half_at(1), half_at(2), half_at(3))
+            }
+            GraphicsColorFormat::R32F => ColorF32::new(f32_at(0), 0., 0., 1.),
+            GraphicsColorFormat::RG32F => ColorF32::new(f32_at(0), f32_at(1), 0., 1.),
+            GraphicsColorFormat::RGBA32F => {
+                ColorF32::new(f32_at(0), f32_at(1), f32_at(2), f32_at(3))
+            }
+            GraphicsColorFormat::DXT1
+            | GraphicsColorFormat::DXT3
+            | GraphicsColorFormat::DXT5
+            | GraphicsColorFormat::UNKNOWN => return None,
+        };
+        Some(color)
+    }
+}
+
+impl From<Color> for ColorF32 {
+    fn from(color: Color) -> Self {
+        ColorF32 {
+            r: color.r as f32 / 255.,
+            g: color.g as f32 / 255.,
+            b: color.b as f32 / 255.,
+            a: color.a as f32 / 255.,
+        }
+    }
+}
+
+impl From<ColorF32> for Color {
+    /// Channels are clamped to `0.0..=1.0` before converting.
+    fn from(color: ColorF32) -> Self {
+        Color {
+            r: unorm8(color.r),
+            g: unorm8(color.g),
+            b: unorm8(color.b),
+            a: unorm8(color.a),
+        }
+    }
+}
+
+/// Quantize a `0.0..=1.0` value to an unsigned normalized integer of `bits`.
+fn unorm(c: f32, bits: u32) -> u32 {
+    let max = ((1u64 << bits) - 1) as f32;
+    (c.clamp(0., 1.) * max).round() as u32
+}
+
+fn unorm8(c: f32) -> u8 {
+    unorm(c, 8) as u8
+}
+
+/// Convert to an IEEE 754 half precision float, rounding to nearest.
+fn f32_to_f16(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exponent = ((bits >> 23) & 0xff) as i32;
+    let mantissa = bits & 0x7f_ffff;
+
+    if exponent == 0xff {
+        // infinity or NaN
+        let nan = if mantissa != 0 { 0x200 } else { 0 };
+        return sign | 0x7c00 | nan;
+    }
+
+    let exponent = exponent - 127 + 15;
+    if exponent >= 0x1f {
+        // too large, round to infinity
+        sign | 0x7c00
+    } else if exponent <= 0 {
+        if exponent < -10 {
+            // too small, round to zero
+            return sign;
+        }
+        // subnormal
+        let mantissa = mantissa | 0x80_0000;
+        let shift = (14 - exponent) as u32;
+        let half = mantissa >> shift;
+        let round = (mantissa >> (shift - 1)) & 1;
+        sign | (half + round) as u16
+    } else {
+        let half = ((exponent as u32) << 10) | (mantissa >> 13);
+        let round = (mantissa >> 12) & 1;
+        sign | (half + round) as u16
+    }
+}
+
+fn f16_to_f32(half: u16) -> f32 {
+    let sign = ((half & 0x8000) as u32) << 16;
+    let exponent = ((half >> 10) & 0x1f) as u32;
+    let mantissa = (half & 0x3ff) as u32;
+
+    let bits = if exponent == 0 {
+        if mantissa == 0 {
+            sign
+        } else {
+            // subnormal, normalize it
+            let mut exponent = 127 - 15 + 1;
+            let mut mantissa = mantissa;
+            while mantissa & 0x400 == 0 {
+                mantissa <<= 1;
+                exponent -= 1;
+            }
+            sign | (exponent << 23) | ((mantissa & 0x3ff) << 13)
+        }
+    } else if exponent == 0x1f {
+        sign | 0x7f80_0000 | (mantissa << 13)
+    } else {
+        sign | ((exponent + 127 - 15) << 23) | (mantissa << 13)
+    };
+    f32::from_bits(bits)
+}
+
 /// A reference to a display, inner pointer is not managed by reference count.
 /// So no `Clone` is implemented. you might want to use `Arc<DisplayRef>` if you need to clone it.
 pub struct DisplayRef {
@@ -89,7 +408,59 @@ pub struct DisplayRef {
 
 impl_ptr_wrapper!(@ptr: inner, DisplayRef, obs_display_t, @identity, obs_display_destroy);
 
+/// A native window which a display can be created for.
+#[cfg(any(target_os = "linux", target_os = "freebsd"))]
+#[derive(Debug, Clone, Copy)]
+pub enum WindowHandle {
+    /// An X11 window id. The window must belong to the X11 connection OBS
+    /// is using, see `obs_get_nix_platform_display`.
+    X11(u32),
+    /// A `wl_surface` pointer on OBS's Wayland connection.
+    Wayland(*mut std::ffi::c_void),
+}
+
 impl DisplayRef {
+    /// Create a display which renders into `window`, e.g. for a custom
+    /// projector or preview.
+    ///
+    /// Nothing is drawn until a callback is added with
+    /// [`add_draw_callback`](Self::add_draw_callback).
+    #[cfg(any(target_os = "linux", target_os = "freebsd"))]
+    pub fn new(window: WindowHandle, cx: u32, cy: u32, background: Color) -> Option<Self> {
+        use obs_sys::{
+            gs_color_format_GS_BGRA, gs_zstencil_format_GS_ZS_NONE, obs_get_nix_platform,
+            obs_get_nix_platform_display, obs_nix_platform_type_OBS_NIX_PLATFORM_WAYLAND,
+        };
+
+        let mut info = gs_init_data {
+            cx,
+            cy,
+            format: gs_color_format_GS_BGRA,
+            zsformat: gs_zstencil_format_GS_ZS_NONE,
+            ..Default::default()
+        };
+        unsafe {
+            match window {
+                WindowHandle::X11(id) => {
+                    if obs_get_nix_platform() == obs_nix_platform_type_OBS_NIX_PLATFORM_WAYLAND {
+                        return None;
+                    }
+                    info.window.id = id;
+                    info.window.display = obs_get_nix_platform_display();
+                }
+                WindowHandle::Wayland(surface) => {
+                    if obs_get_nix_platform() != obs_nix_platform_type_OBS_NIX_PLATFORM_WAYLAND
+                        || surface.is_null()
+                    {
+                        return None;
+                    }
+                    info.window.display = surface;
+                }
+            }
+            Self::from_raw_unchecked(obs_display_create(&info, background.as_rgba()))
+        }
+    }
+
     pub fn enabled(&self) -> bool {
         unsafe { obs_display_enabled(self.inner) }
     }
@@ -139,6 +510,44 @@ impl DrawCallback for RenderMainTexture {
     }
 }
 
+/// Draws a source scaled to fit the display, keeping its aspect ratio.
+pub struct RenderSource(pub SourceRef);
+
+impl DrawCallback for RenderSource {
+    fn draw(&self, cx: u32, cy: u32) {
+        render_source(&self.0, cx, cy)
+    }
+}
+
+/// Draw `source` scaled to fit a `cx` by `cy` area, keeping its aspect ratio
+/// and centering it. Meant to be called from [`DrawCallback::draw`].
+pub fn render_source(source: &SourceRef, cx: u32, cy: u32) {
+    unsafe {
+        let source = source.as_ptr_mut();
+        let width = obs_source_get_width(source);
+        let height = obs_source_get_height(source);
+        if width == 0 || height == 0 || cx == 0 || cy == 0 {
+            return;
+        }
+
+        let scale = (cx as f32 / width as f32).min(cy as f32 / height as f32);
+        let scaled_cx = (width as f32 * scale) as i32;
+        let scaled_cy = (height as f32 * scale) as i32;
+        let x = (cx as i32 - scaled_cx) / 2;
+        let y = (cy as i32 - scaled_cy) / 2;
+
+        gs_projection_push();
+        gs_viewport_push();
+        gs_set_viewport(x, y, scaled_cx, scaled_cy);
+        gs_ortho(0., width as f32, 0., height as f32, -100., 100.);
+
+        obs_source_video_render(source);
+
+        gs_viewport_pop();
+        gs_projection_pop();
+    }
+}
+
 pub trait DrawCallback {
     fn draw(&self, cx: u32, cy: u32);
 }
@@ -175,7 +584,7 @@ impl<'a, S> DrawCallbackId<'a, S> {
         assert_eq!(self.display, display.inner);
         let ptr = self.data;
         unsafe {
-            obs_display_add_draw_callback(
+            obs_display_remove_draw_callback(
                 self.display,
                 Some(std::mem::transmute(self.callback)),
                 ptr as *mut std::ffi::c_void,
@@ -206,3 +615,120 @@ impl<'a, S> Drop for DrawCallbackId<'a, S> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn f16_known_values() {
+        assert_eq!(f32_to_f16(0.), 0x0000);
+        assert_eq!(f32_to_f16(-0.), 0x8000);
+        assert_eq!(f32_to_f16(1.), 0x3c00);
+        assert_eq!(f32_to_f16(-2.), 0xc000);
+        assert_eq!(f32_to_f16(0.5), 0x3800);
+        assert_eq!(f32_to_f16(65504.), 0x7bff);
+        assert_eq!(f16_to_f32(0x3c00), 1.);
+        assert_eq!(f16_to_f32(0x3555), 0.333_251_95);
+        assert_eq!(f16_to_f32(0x7bff), 65504.);
+    }
+
+    #[test]
+    fn f16_special_values() {
+        assert_eq!(f32_to_f16(f32::INFINITY), 0x7c00);
+        assert_eq!(f32_to_f16(f32::NEG_INFINITY), 0xfc00);
+        // too large for a half rounds to infinity
+        assert_eq!(f32_to_f16(65520.), 0x7c00);
+        assert_eq!(f32_to_f16(1e10), 0x7c00);
+        assert_eq!(f16_to_f32(0x7c00), f32::INFINITY);
+        assert_eq!(f16_to_f32(0xfc00), f32::NEG_INFINITY);
+
+        let nan = f32_to_f16(f32::NAN);
+        assert_eq!(nan & 0x7c00, 0x7c00);
+        assert_ne!(nan & 0x3ff, 0);
+        assert!(f16_to_f32(nan).is_nan());
+    }
+
+    #[test]
+    fn f16_subnormals() {
+        let smallest = 2f32.powi(-24);
+        assert_eq!(f32_to_f16(smallest), 0x0001);
+        assert_eq!(f16_to_f32(0x0001), smallest);
+        assert_eq!(f16_to_f32(0x03ff), 1023. * smallest);
+        assert_eq!(f32_to_f16(1023. * smallest), 0x03ff);
+        assert_eq!(f32_to_f16(-smallest), 0x8001);
+        // too small for a half rounds to zero
+        assert_eq!(f32_to_f16(2f32.powi(-30)), 0x0000);
+    }
+
+    #[test]
+    fn f16_round_trip() {
+        for half in 0..=u16::MAX {
+            let value = f16_to_f32(half);
+            if value.is_nan() {
+                continue;
+            }
+            assert_eq!(f32_to_f16(value), half, "{:#06x} -> {}", half, value);
+        }
+    }
+
+    #[test]
+    fn packs_known_values() {
+        let color = Color::new(255, 128, 0, 255);
+        assert_eq!(
+            color.to_bytes(GraphicsColorFormat::RGBA),
+            Some(vec![255, 128, 0, 255])
+        );
+        assert_eq!(
+            color.to_bytes(GraphicsColorFormat::BGRA),
+            Some(vec![0, 128, 255, 255])
+        );
+        assert_eq!(
+            Color::WHITE.to_bytes(GraphicsColorFormat::R10G10B10A2),
+            Some(u32::MAX.to_le_bytes().to_vec())
+        );
+        assert_eq!(
+            Color::WHITE.to_bytes(GraphicsColorFormat::RGBA16F),
+            Some(
+                [0x3c00u16; 4]
+                    .iter()
+                    .flat_map(|h| h.to_le_bytes())
+                    .collect()
+            )
+        );
+        assert_eq!(
+            ColorF32::new(0.25, 0., 0., 1.).to_bytes(GraphicsColorFormat::R32F),
+            Some(0.25f32.to_le_bytes().to_vec())
+        );
+        assert_eq!(Color::WHITE.to_bytes(GraphicsColorFormat::DXT1), None);
+        assert_eq!(Color::WHITE.to_bytes(GraphicsColorFormat::UNKNOWN), None);
+    }
+
+    #[test]
+    fn packing_round_trips() {
+        use GraphicsColorFormat::*;
+        let color = Color::new(12, 200, 99, 255);
+        for format in [RGBA, BGRA, R10G10B10A2, RGBA16, RGBA16F, RGBA32F] {
+            let bytes = color.to_bytes(format).unwrap();
+            assert_eq!(Some(bytes.len()), format.bytes_per_pixel());
+            assert_eq!(
+                Color::from_bytes(format, &bytes),
+                Some(color),
+                "{:?}",
+                format
+            );
+        }
+        // formats without alpha read back as opaque
+        let bytes = color.to_bytes(BGRX).unwrap();
+        assert_eq!(Color::from_bytes(BGRX, &bytes), Some(color));
+        assert_eq!(Color::from_bytes(RGBA, &[0; 3]), None);
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn as_format_matches_packed_u32() {
+        let color = Color::new(1, 2, 3, 4);
+        assert_eq!(color.as_format(GraphicsColorFormat::RGBA), color.as_rgba());
+        assert_eq!(color.as_format(GraphicsColorFormat::BGRA), color.as_bgra());
+    }
+}
@@ -0,0 +1,137 @@
+use std::path::Path;
+
+use obs_sys::{
+    gs_color_space_GS_CS_SRGB, gs_image_alpha_mode, gs_image_alpha_mode_GS_IMAGE_ALPHA_PREMULTIPLY,
+    gs_image_alpha_mode_GS_IMAGE_ALPHA_PREMULTIPLY_SRGB,
+    gs_image_alpha_mode_GS_IMAGE_ALPHA_STRAIGHT, gs_image_file4, gs_image_file4_init,
+    gs_image_file_free, gs_image_file_init_texture, gs_image_file_t, gs_image_file_tick,
+    gs_image_file_update_texture,
+};
+
+// The `gs_image_file4_*` functions besides `init` are `static inline` in
+// image-file.h, so they're not in the bindings. These do the same.
+
+unsafe fn gs_image_file4_free(if4: *mut gs_image_file4) {
+    gs_image_file_free(&mut (*if4).image3.image2.image);
+    (*if4).image3.image2.mem_usage = 0;
+}
+
+unsafe fn gs_image_file4_init_texture(if4: *mut gs_image_file4) {
+    gs_image_file_init_texture(&mut (*if4).image3.image2.image);
+}
+
+unsafe fn gs_image_file4_tick(if4: *mut gs_image_file4, elapsed_time_ns: u64) -> bool {
+    gs_image_file_tick(&mut (*if4).image3.image2.image, elapsed_time_ns)
+}
+
+unsafe fn gs_image_file4_update_texture(if4: *mut gs_image_file4) {
+    gs_image_file_update_texture(&mut (*if4).image3.image2.image);
+}
+
+use super::{GraphicsColorFormat, GraphicsGuard, GraphicsTextureRef};
+use crate::{native_enum, string::TryIntoObsString, Error, Result};
+
+native_enum!(GraphicsImageAlphaMode, gs_image_alpha_mode {
+    Straight => GS_IMAGE_ALPHA_STRAIGHT,
+    PremultiplySrgb => GS_IMAGE_ALPHA_PREMULTIPLY_SRGB,
+    Premultiply => GS_IMAGE_ALPHA_PREMULTIPLY,
+});
+
+/// Wrapper around `gs_image_file4_t`, an image (PNG, JPG, GIF, ...) loaded
+/// from disk using OBS's image loader.
+///
+/// Decoding happens in [`new`](Self::new), which does not need the graphics
+/// context and can be called from any thread. The texture is created
+/// separately by [`init_texture`](Self::init_texture).
+///
+/// Animated GIFs are advanced with [`tick`](Self::tick) (usually from
+/// `video_tick`), followed by [`update_texture`](Self::update_texture) when it
+/// returns `true`.
+pub struct GraphicsImage {
+    raw: Box<gs_image_file4>,
+}
+
+impl GraphicsImage {
+    /// Load and decode the image at `path`.
+    pub fn new(path: &Path, alpha_mode: GraphicsImageAlphaMode) -> Result<Self> {
+        let path = path.try_into_obs_string()?;
+        let mut raw = Box::<gs_image_file4>::default();
+        unsafe {
+            gs_image_file4_init(
+                &mut *raw,
+                path.as_ptr(),
+                alpha_mode.as_raw(),
+                gs_color_space_GS_CS_SRGB,
+            );
+        }
+        let image = Self { raw };
+        if !image.inner().loaded {
+            return Err(Error::ObsError(-1));
+        }
+        Ok(image)
+    }
+
+    fn inner(&self) -> &gs_image_file_t {
+        &self.raw.image3.image2.image
+    }
+
+    /// Create the texture for the decoded image. Must be called before
+    /// [`texture`](Self::texture) returns anything.
+    pub fn init_texture(&mut self) {
+        let image = &mut *self.raw;
+        GraphicsGuard::with_enter(|| unsafe {
+            gs_image_file4_init_texture(image);
+        });
+    }
+
+    /// Advance an animated image by `elapsed_ns` nanoseconds. Returns `true`
+    /// if the current frame changed and the texture should be updated with
+    /// [`update_texture`](Self::update_texture).
+    pub fn tick(&mut self, elapsed_ns: u64) -> bool {
+        unsafe { gs_image_file4_tick(&mut *self.raw, elapsed_ns) }
+    }
+
+    /// Upload the current animation frame to the texture.
+    pub fn update_texture(&mut self) {
+        let image = &mut *self.raw;
+        GraphicsGuard::with_enter(|| unsafe {
+            gs_image_file4_update_texture(image);
+        });
+    }
+
+    /// The texture of the image, if [`init_texture`](Self::init_texture) has
+    /// been called.
+    pub fn texture(&self) -> Option<GraphicsTextureRef<'_>> {
+        unsafe { GraphicsTextureRef::from_raw(self.inner().texture) }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.inner().cx
+    }
+
+    pub fn height(&self) -> u32 {
+        self.inner().cy
+    }
+
+    pub fn format(&self) -> GraphicsColorFormat {
+        GraphicsColorFormat::from_raw(self.inner().format).unwrap_or(GraphicsColorFormat::UNKNOWN)
+    }
+
+    pub fn is_animated(&self) -> bool {
+        self.inner().is_animated_gif
+    }
+
+    /// Approximate memory used by the decoded image, in bytes.
+    pub fn mem_usage(&self) -> u64 {
+        self.raw.image3.image2.mem_usage
+    }
+}
+
+impl Drop for GraphicsImage {
+    fn drop(&mut self) {
+        let image = &mut *self.raw;
+        GraphicsGuard::with_enter(|| unsafe {
+            gs_image_file4_free(image);
+        });
+    }
+}
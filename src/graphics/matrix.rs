@@ -0,0 +1,323 @@
+use obs_sys::{matrix4, quat};
+
+use super::{Vec3, Vec4};
+
+/// A rotation, with the same layout as libobs' `quat`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[repr(C, align(16))]
+pub struct Quat {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub w: f32,
+}
+
+impl Quat {
+    pub const IDENTITY: Quat = Quat {
+        x: 0.,
+        y: 0.,
+        z: 0.,
+        w: 1.,
+    };
+
+    pub fn new(x: f32, y: f32, z: f32, w: f32) -> Self {
+        Self { x, y, z, w }
+    }
+
+    /// A rotation of `angle` radians around `axis`, which should be
+    /// normalized.
+    pub fn from_axis_angle(axis: &Vec3, angle: f32) -> Self {
+        let (sine, cosine) = (angle * 0.5).sin_cos();
+        Self {
+            x: axis.x() * sine,
+            y: axis.y() * sine,
+            z: axis.z() * sine,
+            w: cosine,
+        }
+    }
+
+    /// The axis and angle in radians of the rotation.
+    pub fn to_axis_angle(&self) -> (Vec3, f32) {
+        let len = (self.x * self.x + self.y * self.y + self.z * self.z).sqrt();
+        if len == 0. {
+            (Vec3::new(0., 0., 0.), 0.)
+        } else {
+            let axis = Vec3::new(self.x / len, self.y / len, self.z / len);
+            (axis, 2. * self.w.clamp(-1., 1.).acos())
+        }
+    }
+
+    pub fn dot(&self, other: &Quat) -> f32 {
+        self.x * other.x + self.y * other.y + self.z * other.z + self.w * other.w
+    }
+
+    pub fn length(&self) -> f32 {
+        self.dot(self).sqrt()
+    }
+
+    pub fn normalize(&self) -> Self {
+        let len = self.length();
+        if len == 0. {
+            Self::IDENTITY
+        } else {
+            Self::new(self.x / len, self.y / len, self.z / len, self.w / len)
+        }
+    }
+
+    pub fn inverse(&self) -> Self {
+        let norm = self.dot(self);
+        if norm == 0. {
+            Self::IDENTITY
+        } else {
+            Self::new(
+                -self.x / norm,
+                -self.y / norm,
+                -self.z / norm,
+                self.w / norm,
+            )
+        }
+    }
+
+    pub fn as_ptr(&self) -> *const quat {
+        self as *const Self as *const quat
+    }
+}
+
+impl Default for Quat {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl std::ops::Mul for Quat {
+    type Output = Quat;
+
+    /// Combine two rotations, matching libobs' `quat_mul`. `a * b` rotates
+    /// by `b` first and then by `a`, unlike [`Matrix4`] multiplication.
+    fn mul(self, rhs: Quat) -> Quat {
+        Quat {
+            x: rhs.x * self.w + self.x * rhs.w + (self.y * rhs.z - self.z * rhs.y),
+            y: rhs.y * self.w + self.y * rhs.w + (self.z * rhs.x - self.x * rhs.z),
+            z: rhs.z * self.w + self.z * rhs.w + (self.x * rhs.y - self.y * rhs.x),
+            w: self.w * rhs.w - (self.x * rhs.x + self.y * rhs.y + self.z * rhs.z),
+        }
+    }
+}
+
+/// A 4x4 transform matrix, with the same layout as libobs' `matrix4`.
+///
+/// Like libobs, vectors are treated as rows: `x`, `y` and `z` are the
+/// transformed axes and `t` is the translation. Methods such as
+/// [`translate`](Self::translate) apply the transform after the existing one,
+/// matching `matrix4_translate3v` and friends.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[repr(C)]
+pub struct Matrix4 {
+    pub x: Vec4,
+    pub y: Vec4,
+    pub z: Vec4,
+    pub t: Vec4,
+}
+
+impl Matrix4 {
+    pub fn identity() -> Self {
+        Self {
+            x: Vec4::new(1., 0., 0., 0.),
+            y: Vec4::new(0., 1., 0., 0.),
+            z: Vec4::new(0., 0., 1., 0.),
+            t: Vec4::new(0., 0., 0., 1.),
+        }
+    }
+
+    pub fn from_translation(offset: &Vec3) -> Self {
+        Self {
+            t: Vec4::from_vec3(offset, 1.),
+            ..Self::identity()
+        }
+    }
+
+    pub fn from_scale(scale: &Vec3) -> Self {
+        Self {
+            x: Vec4::new(scale.x(), 0., 0., 0.),
+            y: Vec4::new(0., scale.y(), 0., 0.),
+            z: Vec4::new(0., 0., scale.z(), 0.),
+            t: Vec4::new(0., 0., 0., 1.),
+        }
+    }
+
+    /// Rotation matrix for `rotation`, matching libobs' `matrix4_from_quat`.
+    pub fn from_quat(rotation: &Quat) -> Self {
+        let q = rotation;
+        let norm = q.dot(q);
+        let s = if norm > 0. { 2. / norm } else { 0. };
+
+        let xx = q.x * q.x * s;
+        let yy = q.y * q.y * s;
+        let zz = q.z * q.z * s;
+        let xy = q.x * q.y * s;
+        let xz = q.x * q.z * s;
+        let yz = q.y * q.z * s;
+        let wx = q.w * q.x * s;
+        let wy = q.w * q.y * s;
+        let wz = q.w * q.z * s;
+
+        Self {
+            x: Vec4::new(1. - (yy + zz), xy + wz, xz - wy, 0.),
+            y: Vec4::new(xy - wz, 1. - (xx + zz), yz + wx, 0.),
+            z: Vec4::new(xz + wy, yz - wx, 1. - (xx + yy), 0.),
+            t: Vec4::new(0., 0., 0., 1.),
+        }
+    }
+
+    pub fn translate(&self, offset: &Vec3) -> Self {
+        *self * Self::from_translation(offset)
+    }
+
+    pub fn scale(&self, scale: &Vec3) -> Self {
+        *self * Self::from_scale(scale)
+    }
+
+    pub fn rotate(&self, rotation: &Quat) -> Self {
+        *self * Self::from_quat(rotation)
+    }
+
+    /// Rotate by `angle` radians around `axis`.
+    pub fn rotate_axis_angle(&self, axis: &Vec3, angle: f32) -> Self {
+        self.rotate(&Quat::from_axis_angle(axis, angle))
+    }
+
+    pub fn transpose(&self) -> Self {
+        let Self { x, y, z, t } = self;
+        Self {
+            x: Vec4::new(x.x(), y.x(), z.x(), t.x()),
+            y: Vec4::new(x.y(), y.y(), z.y(), t.y()),
+            z: Vec4::new(x.z(), y.z(), z.z(), t.z()),
+            t: Vec4::new(x.w(), y.w(), z.w(), t.w()),
+        }
+    }
+
+    /// Transform a row vector by the matrix.
+    pub fn transform(&self, v: &Vec4) -> Vec4 {
+        self.x * v.x() + self.y * v.y() + self.z * v.z() + self.t * v.w()
+    }
+
+    /// Transform a point, treating it as having `w = 1`.
+    pub fn transform_point(&self, v: &Vec3) -> Vec3 {
+        let out = self.transform(&Vec4::from_vec3(v, 1.));
+        Vec3::new(out.x(), out.y(), out.z())
+    }
+
+    pub fn as_ptr(&self) -> *const matrix4 {
+        self as *const Self as *const matrix4
+    }
+
+    pub fn as_mut_ptr(&mut self) -> *mut matrix4 {
+        self as *mut Self as *mut matrix4
+    }
+}
+
+impl Default for Matrix4 {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl std::ops::Mul for Matrix4 {
+    type Output = Matrix4;
+
+    /// Apply `self` then `rhs`, matching libobs' `matrix4_mul`.
+    fn mul(self, rhs: Matrix4) -> Matrix4 {
+        Matrix4 {
+            x: rhs.transform(&self.x),
+            y: rhs.transform(&self.y),
+            z: rhs.transform(&self.z),
+            t: rhs.transform(&self.t),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use super::*;
+
+    fn assert_near(a: &Vec3, b: &Vec3) {
+        let d = *a - *b;
+        assert!(d.length() < 1e-5, "{:?} != {:?}", a, b);
+    }
+
+    fn assert_quat_near(a: &Quat, b: &Quat) {
+        // q and -q are the same rotation
+        assert!(a.dot(b).abs() > 1. - 1e-5, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn identity() {
+        let p = Vec3::new(1., -2., 3.);
+        assert_near(&Matrix4::identity().transform_point(&p), &p);
+        assert_near(&Matrix4::from_quat(&Quat::IDENTITY).transform_point(&p), &p);
+        assert_eq!(
+            Matrix4::identity() * Matrix4::identity(),
+            Matrix4::identity()
+        );
+        assert_eq!(Quat::IDENTITY * Quat::IDENTITY, Quat::IDENTITY);
+    }
+
+    #[test]
+    fn rotation_about_z() {
+        let z = Vec3::new(0., 0., 1.);
+        let rotation = Matrix4::identity().rotate_axis_angle(&z, FRAC_PI_2);
+        assert_near(
+            &rotation.transform_point(&Vec3::new(1., 0., 0.)),
+            &Vec3::new(0., 1., 0.),
+        );
+        assert_near(
+            &rotation.transform_point(&Vec3::new(0., 1., 0.)),
+            &Vec3::new(-1., 0., 0.),
+        );
+        assert_near(&rotation.transform_point(&z), &z);
+    }
+
+    #[test]
+    fn transforms_apply_in_order() {
+        let z = Vec3::new(0., 0., 1.);
+        let m = Matrix4::identity()
+            .scale(&Vec3::new(2., 2., 2.))
+            .rotate_axis_angle(&z, FRAC_PI_2)
+            .translate(&Vec3::new(10., 0., 0.));
+        // scaled to (2, 0, 0), rotated to (0, 2, 0), then moved
+        assert_near(
+            &m.transform_point(&Vec3::new(1., 0., 0.)),
+            &Vec3::new(10., 2., 0.),
+        );
+    }
+
+    #[test]
+    fn quat_inverse_round_trip() {
+        let axis = Vec3::new(1., 2., 3.).normalize();
+        let q = Quat::from_axis_angle(&axis, 0.7);
+        assert_quat_near(&(q * q.inverse()), &Quat::IDENTITY);
+        assert_quat_near(&(q.inverse() * q), &Quat::IDENTITY);
+
+        let p = Vec3::new(0.5, -1., 4.);
+        let there = Matrix4::from_quat(&q).transform_point(&p);
+        let back = Matrix4::from_quat(&q.inverse()).transform_point(&there);
+        assert_near(&back, &p);
+
+        let (out_axis, angle) = q.to_axis_angle();
+        assert_near(&out_axis, &axis);
+        assert!((angle - 0.7).abs() < 1e-5);
+    }
+
+    #[test]
+    fn quat_mul_matches_matrix_mul() {
+        let a = Quat::from_axis_angle(&Vec3::new(0., 0., 1.), 0.4);
+        let b = Quat::from_axis_angle(&Vec3::new(1., 0., 0.), 1.1);
+        let p = Vec3::new(1., 2., 3.);
+        let combined = Matrix4::from_quat(&(a * b)).transform_point(&p);
+        // `a * b` applies `b` first, matrices apply the left side first
+        let separate = (Matrix4::from_quat(&b) * Matrix4::from_quat(&a)).transform_point(&p);
+        assert_near(&combined, &separate);
+    }
+}
@@ -1,10 +1,29 @@
 pub mod display;
+pub mod image;
+pub mod matrix;
+pub mod pool;
+pub mod reload;
+pub mod stagesurf;
+pub mod state;
+pub mod texrender;
+#[cfg(feature = "text")]
+pub mod text;
+pub mod vertex;
+
+pub use image::*;
+pub use matrix::*;
+pub use pool::*;
+pub use reload::*;
+pub use stagesurf::*;
+pub use state::*;
+pub use texrender::*;
+pub use vertex::*;
 
 use crate::{native_enum, Error, Result};
 use core::convert::TryFrom;
 use core::ptr::null_mut;
 use obs_sys::{
-    gs_address_mode, gs_address_mode_GS_ADDRESS_BORDER, gs_address_mode_GS_ADDRESS_CLAMP,
+    bfree, gs_address_mode, gs_address_mode_GS_ADDRESS_BORDER, gs_address_mode_GS_ADDRESS_CLAMP,
     gs_address_mode_GS_ADDRESS_MIRROR, gs_address_mode_GS_ADDRESS_MIRRORONCE,
     gs_address_mode_GS_ADDRESS_WRAP, gs_color_format, gs_color_format_GS_A8,
     gs_color_format_GS_BGRA, gs_color_format_GS_BGRX, gs_color_format_GS_DXT1,
@@ -13,10 +32,16 @@ use obs_sys::{
     gs_color_format_GS_R8, gs_color_format_GS_R8G8, gs_color_format_GS_RG16F,
     gs_color_format_GS_RG32F, gs_color_format_GS_RGBA, gs_color_format_GS_RGBA16,
     gs_color_format_GS_RGBA16F, gs_color_format_GS_RGBA32F, gs_color_format_GS_UNKNOWN,
-    gs_effect_create, gs_effect_destroy, gs_effect_get_param_by_name, gs_effect_get_param_info,
-    gs_effect_param_info, gs_effect_set_next_sampler, gs_effect_set_texture, gs_effect_set_vec2,
-    gs_effect_t, gs_eparam_t, gs_sample_filter, gs_sample_filter_GS_FILTER_ANISOTROPIC,
-    gs_sample_filter_GS_FILTER_LINEAR, gs_sample_filter_GS_FILTER_MIN_LINEAR_MAG_MIP_POINT,
+    gs_color_space, gs_color_space_GS_CS_709_EXTENDED, gs_color_space_GS_CS_709_SCRGB,
+    gs_color_space_GS_CS_SRGB, gs_color_space_GS_CS_SRGB_16F, gs_effect_create,
+    gs_effect_create_from_file, gs_effect_destroy, gs_effect_get_default_val,
+    gs_effect_get_default_val_size, gs_effect_get_num_params, gs_effect_get_param_by_idx,
+    gs_effect_get_param_by_name, gs_effect_get_param_info, gs_effect_get_technique,
+    gs_effect_param_info, gs_effect_set_matrix4, gs_effect_set_next_sampler, gs_effect_set_texture,
+    gs_effect_set_vec2, gs_effect_set_vec3, gs_effect_set_vec4, gs_effect_t, gs_eparam_t,
+    gs_param_get_annotation_by_idx, gs_param_get_annotation_by_name, gs_param_get_num_annotations,
+    gs_sample_filter, gs_sample_filter_GS_FILTER_ANISOTROPIC, gs_sample_filter_GS_FILTER_LINEAR,
+    gs_sample_filter_GS_FILTER_MIN_LINEAR_MAG_MIP_POINT,
     gs_sample_filter_GS_FILTER_MIN_LINEAR_MAG_POINT_MIP_LINEAR,
     gs_sample_filter_GS_FILTER_MIN_MAG_LINEAR_MIP_POINT,
     gs_sample_filter_GS_FILTER_MIN_MAG_POINT_MIP_LINEAR,
@@ -30,20 +55,33 @@ use obs_sys::{
     gs_shader_param_type_GS_SHADER_PARAM_STRING, gs_shader_param_type_GS_SHADER_PARAM_TEXTURE,
     gs_shader_param_type_GS_SHADER_PARAM_UNKNOWN, gs_shader_param_type_GS_SHADER_PARAM_VEC2,
     gs_shader_param_type_GS_SHADER_PARAM_VEC3, gs_shader_param_type_GS_SHADER_PARAM_VEC4,
-    gs_texture_create, gs_texture_destroy, gs_texture_get_height, gs_texture_get_width,
-    gs_texture_map, gs_texture_set_image, gs_texture_t, gs_texture_unmap, obs_allow_direct_render,
+    gs_technique_begin, gs_technique_begin_pass, gs_technique_end, gs_technique_end_pass,
+    gs_technique_t, gs_texture_create, gs_texture_destroy, gs_texture_get_color_format,
+    gs_texture_get_height, gs_texture_get_width, gs_texture_map, gs_texture_set_image,
+    gs_texture_t, gs_texture_unmap, obs_allow_direct_render,
     obs_allow_direct_render_OBS_ALLOW_DIRECT_RENDERING,
-    obs_allow_direct_render_OBS_NO_DIRECT_RENDERING, obs_enter_graphics, obs_leave_graphics,
-    obs_source_draw, vec2, vec3, vec4, GS_DYNAMIC,
+    obs_allow_direct_render_OBS_NO_DIRECT_RENDERING, obs_base_effect,
+    obs_base_effect_OBS_EFFECT_AREA, obs_base_effect_OBS_EFFECT_BICUBIC,
+    obs_base_effect_OBS_EFFECT_BILINEAR_LOWRES, obs_base_effect_OBS_EFFECT_DEFAULT,
+    obs_base_effect_OBS_EFFECT_DEFAULT_RECT, obs_base_effect_OBS_EFFECT_LANCZOS,
+    obs_base_effect_OBS_EFFECT_OPAQUE, obs_base_effect_OBS_EFFECT_PREMULTIPLIED_ALPHA,
+    obs_base_effect_OBS_EFFECT_REPEAT, obs_base_effect_OBS_EFFECT_SOLID, obs_enter_graphics,
+    obs_get_base_effect, obs_leave_graphics, obs_source_draw, vec2, vec3, vec4, GS_DYNAMIC,
 };
 use paste::item;
 use std::{
     ffi::{CStr, CString},
+    marker::PhantomData,
+    mem::ManuallyDrop,
     ptr,
 };
-use std::{os::raw::c_int, slice};
+use std::{
+    os::raw::{c_char, c_int},
+    path::Path,
+    slice,
+};
 
-use super::string::ObsString;
+use super::string::{ObsString, TryIntoObsString};
 
 /// Guard to guarantee that we exit graphics context properly.
 /// This does not prevent one from calling APIs that are not supposed to be
@@ -88,19 +126,138 @@ native_enum!(ShaderParamType, gs_shader_param_type {
     Texture => GS_SHADER_PARAM_TEXTURE,
 });
 
+native_enum!(
+/// Effects built in to OBS, see [`GraphicsEffect::base`]
+GraphicsBaseEffect, obs_base_effect {
+    Default => OBS_EFFECT_DEFAULT,
+    DefaultRect => OBS_EFFECT_DEFAULT_RECT,
+    Opaque => OBS_EFFECT_OPAQUE,
+    Solid => OBS_EFFECT_SOLID,
+    Bicubic => OBS_EFFECT_BICUBIC,
+    Lanczos => OBS_EFFECT_LANCZOS,
+    BilinearLowres => OBS_EFFECT_BILINEAR_LOWRES,
+    PremultipliedAlpha => OBS_EFFECT_PREMULTIPLIED_ALPHA,
+    Repeat => OBS_EFFECT_REPEAT,
+    Area => OBS_EFFECT_AREA,
+});
+
 pub struct GraphicsEffect {
     raw: *mut gs_effect_t,
 }
 
+/// A single diagnostic from the effect compiler, e.g.
+/// `crop_filter.effect (12, 5): Unexpected token`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShaderDiagnostic {
+    pub file: String,
+    pub line: u32,
+    pub column: u32,
+    pub message: String,
+}
+
+impl ShaderDiagnostic {
+    fn parse(line: &str) -> Option<Self> {
+        let (location, message) = line.split_once("): ")?;
+        let (file, position) = location.rsplit_once(" (")?;
+        let (row, column) = position.split_once(',')?;
+        Some(Self {
+            file: file.to_string(),
+            line: row.trim().parse().ok()?,
+            column: column.trim().parse().ok()?,
+            message: message.trim().to_string(),
+        })
+    }
+}
+
+impl std::fmt::Display for ShaderDiagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} ({}, {}): {}",
+            self.file, self.line, self.column, self.message
+        )
+    }
+}
+
+/// Output of a failed effect compilation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShaderCompileOutput {
+    /// The error string as reported by OBS.
+    pub raw: String,
+    /// The lines of `raw` which could be parsed as diagnostics.
+    pub diagnostics: Vec<ShaderDiagnostic>,
+}
+
+impl ShaderCompileOutput {
+    pub fn parse(raw: String) -> Self {
+        let diagnostics = raw.lines().filter_map(ShaderDiagnostic::parse).collect();
+        Self { raw, diagnostics }
+    }
+}
+
+impl std::fmt::Display for ShaderCompileOutput {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let raw = self.raw.trim();
+        if raw.is_empty() {
+            f.write_str("no compiler output")
+        } else {
+            f.write_str(raw)
+        }
+    }
+}
+
+/// Take ownership of an error string allocated by OBS, freeing it.
+unsafe fn take_error_string(raw: *mut c_char) -> Option<String> {
+    if raw.is_null() {
+        return None;
+    }
+    let message = CStr::from_ptr(raw).to_string_lossy().into_owned();
+    bfree(raw as *mut _);
+    Some(message)
+}
+
 impl GraphicsEffect {
-    pub fn from_effect_string(value: ObsString, name: ObsString) -> Option<Self> {
+    /// Compile an effect from source. `name` is used in the compiler output
+    /// and should usually be the file name of the effect.
+    ///
+    /// Returns [`Error::ShaderCompile`] with the compiler output if the effect
+    /// fails to compile.
+    pub fn from_effect_string(value: ObsString, name: ObsString) -> Result<Self> {
+        let mut error = null_mut();
         let raw = GraphicsGuard::with_enter(|| unsafe {
-            gs_effect_create(value.as_ptr(), name.as_ptr(), std::ptr::null_mut())
+            gs_effect_create(value.as_ptr(), name.as_ptr(), &mut error)
         });
+        let message = unsafe { take_error_string(error) };
         if raw.is_null() {
-            None
+            Err(Error::ShaderCompile {
+                name: name.as_str().to_string(),
+                message: ShaderCompileOutput::parse(message.unwrap_or_default()),
+            })
         } else {
-            Some(Self { raw })
+            Ok(Self { raw })
+        }
+    }
+
+    /// Load an effect from a file using
+    /// [`gs_effect_create_from_file`](https://obsproject.com/docs/reference-libobs-graphics-effects.html#c.gs_effect_create_from_file).
+    ///
+    /// Note that OBS caches effects by file name, so calling this again with
+    /// the same path returns the already loaded effect. Use
+    /// [`ReloadableEffect`] to pick up changes to the file.
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let path = path.try_into_obs_string()?;
+        let mut error = null_mut();
+        let raw = GraphicsGuard::with_enter(|| unsafe {
+            gs_effect_create_from_file(path.as_ptr(), &mut error)
+        });
+        let message = unsafe { take_error_string(error) };
+        if raw.is_null() {
+            Err(Error::ShaderCompile {
+                name: path.as_str().to_string(),
+                message: ShaderCompileOutput::parse(message.unwrap_or_default()),
+            })
+        } else {
+            Ok(Self { raw })
         }
     }
 
@@ -118,6 +275,44 @@ impl GraphicsEffect {
         }
     }
 
+    /// Iterate over every parameter (uniform) declared by the effect, in
+    /// declaration order.
+    pub fn params(&self) -> GraphicsEffectParams<'_> {
+        let count = unsafe { gs_effect_get_num_params(self.raw) };
+        GraphicsEffectParams {
+            effect: self,
+            index: 0,
+            count,
+        }
+    }
+
+    /// Look up a technique by name, e.g. `"Draw"`.
+    pub fn get_technique(&mut self, name: ObsString) -> Option<GraphicsEffectTechnique<'_>> {
+        let raw = unsafe { gs_effect_get_technique(self.raw, name.as_ptr()) };
+        if raw.is_null() {
+            None
+        } else {
+            Some(GraphicsEffectTechnique {
+                raw,
+                _effect: PhantomData,
+            })
+        }
+    }
+
+    /// Get one of the effects built in to OBS. These are owned by OBS and live
+    /// as long as the graphics subsystem.
+    pub fn base(effect: GraphicsBaseEffect) -> Option<GraphicsEffectRef<'static>> {
+        let raw = unsafe { obs_get_base_effect(effect.as_raw()) };
+        if raw.is_null() {
+            None
+        } else {
+            Some(GraphicsEffectRef {
+                effect: ManuallyDrop::new(Self { raw }),
+                _owner: PhantomData,
+            })
+        }
+    }
+
     /// # Safety
     /// Returns a mutable pointer to an effect which if modified could cause UB.
     pub unsafe fn as_ptr(&self) -> *mut gs_effect_t {
@@ -125,6 +320,138 @@ impl GraphicsEffect {
     }
 }
 
+/// A [`GraphicsEffect`] which is owned by something else (e.g. OBS itself) and
+/// is not destroyed when dropped.
+pub struct GraphicsEffectRef<'a> {
+    effect: ManuallyDrop<GraphicsEffect>,
+    _owner: PhantomData<&'a ()>,
+}
+
+impl std::ops::Deref for GraphicsEffectRef<'_> {
+    type Target = GraphicsEffect;
+
+    fn deref(&self) -> &GraphicsEffect {
+        &self.effect
+    }
+}
+
+impl std::ops::DerefMut for GraphicsEffectRef<'_> {
+    fn deref_mut(&mut self) -> &mut GraphicsEffect {
+        &mut self.effect
+    }
+}
+
+/// Iterator over the parameters of a [`GraphicsEffect`], created by
+/// [`GraphicsEffect::params`].
+pub struct GraphicsEffectParams<'a> {
+    effect: &'a GraphicsEffect,
+    index: usize,
+    count: usize,
+}
+
+impl Iterator for GraphicsEffectParams<'_> {
+    type Item = GraphicsEffectParam;
+
+    fn next(&mut self) -> Option<GraphicsEffectParam> {
+        while self.index < self.count {
+            let raw = unsafe { gs_effect_get_param_by_idx(self.effect.raw, self.index) };
+            self.index += 1;
+            if !raw.is_null() {
+                return Some(unsafe { GraphicsEffectParam::from_raw(raw) });
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.count - self.index))
+    }
+}
+
+/// A technique of a [`GraphicsEffect`], used to draw with the effect outside of
+/// `process_filter`.
+pub struct GraphicsEffectTechnique<'a> {
+    raw: *mut gs_technique_t,
+    _effect: PhantomData<&'a mut GraphicsEffect>,
+}
+
+impl GraphicsEffectTechnique<'_> {
+    /// Begin the technique, returning a guard which ends it when dropped.
+    pub fn begin(&mut self, _context: &GraphicsEffectContext) -> GraphicsTechniqueGuard<'_> {
+        let passes = unsafe { gs_technique_begin(self.raw) };
+        GraphicsTechniqueGuard {
+            technique: self.raw,
+            passes,
+            _technique: PhantomData,
+        }
+    }
+
+    /// Run `func` once for every pass of the technique, the equivalent of
+    /// `gs_effect_loop`.
+    pub fn for_each_pass<F: FnMut(usize)>(&mut self, context: &GraphicsEffectContext, mut func: F) {
+        let mut technique = self.begin(context);
+        for pass in 0..technique.pass_count() {
+            if let Some(_pass) = technique.begin_pass(pass) {
+                func(pass);
+            }
+        }
+    }
+
+    /// # Safety
+    /// Returns a mutable pointer to a technique which if modified could cause UB.
+    pub unsafe fn as_ptr(&self) -> *mut gs_technique_t {
+        self.raw
+    }
+}
+
+/// Guard returned by [`GraphicsEffectTechnique::begin`].
+pub struct GraphicsTechniqueGuard<'a> {
+    technique: *mut gs_technique_t,
+    passes: usize,
+    _technique: PhantomData<&'a mut ()>,
+}
+
+impl GraphicsTechniqueGuard<'_> {
+    pub fn pass_count(&self) -> usize {
+        self.passes
+    }
+
+    /// Begin a pass by index, returning a guard which ends the pass when
+    /// dropped.
+    pub fn begin_pass(&mut self, pass: usize) -> Option<GraphicsPassGuard<'_>> {
+        if unsafe { gs_technique_begin_pass(self.technique, pass) } {
+            Some(GraphicsPassGuard {
+                technique: self.technique,
+                _guard: PhantomData,
+            })
+        } else {
+            None
+        }
+    }
+}
+
+impl Drop for GraphicsTechniqueGuard<'_> {
+    fn drop(&mut self) {
+        unsafe {
+            gs_technique_end(self.technique);
+        }
+    }
+}
+
+/// Guard returned by [`GraphicsTechniqueGuard::begin_pass`].
+pub struct GraphicsPassGuard<'a> {
+    technique: *mut gs_technique_t,
+    _guard: PhantomData<&'a mut ()>,
+}
+
+impl Drop for GraphicsPassGuard<'_> {
+    fn drop(&mut self) {
+        unsafe {
+            gs_technique_end_pass(self.technique);
+        }
+    }
+}
+
 impl Drop for GraphicsEffect {
     fn drop(&mut self) {
         GraphicsGuard::with_enter(|| unsafe {
@@ -166,6 +493,171 @@ impl GraphicsEffectParam {
     pub fn get_name(&self) -> &str {
         &self.name
     }
+
+    pub fn shader_type(&self) -> ShaderParamType {
+        self.shader_type
+    }
+
+    /// The value the parameter (or annotation) was initialised with in the
+    /// effect file, if any.
+    pub fn default_value(&self) -> Option<ShaderParamValue> {
+        unsafe {
+            let size = gs_effect_get_default_val_size(self.raw);
+            let data = gs_effect_get_default_val(self.raw);
+            if data.is_null() {
+                return None;
+            }
+            let bytes = slice::from_raw_parts(data as *const u8, size);
+            let value = ShaderParamValue::from_bytes(self.shader_type, bytes);
+            bfree(data);
+            value
+        }
+    }
+
+    /// Iterate over the annotations attached to this parameter, e.g.
+    /// `float strength<string label = "Strength"; float minimum = 0.0;>`.
+    pub fn annotations(&self) -> GraphicsEffectAnnotations<'_> {
+        let count = unsafe { gs_param_get_num_annotations(self.raw) };
+        GraphicsEffectAnnotations {
+            param: self,
+            index: 0,
+            count,
+        }
+    }
+
+    pub fn get_annotation_by_name(&self, name: ObsString) -> Option<GraphicsEffectParam> {
+        unsafe {
+            let pointer = gs_param_get_annotation_by_name(self.raw, name.as_ptr());
+            if pointer.is_null() {
+                None
+            } else {
+                Some(GraphicsEffectParam::from_raw(pointer))
+            }
+        }
+    }
+
+    /// Shorthand for reading the value of an annotation by name.
+    pub fn get_annotation_value(&self, name: ObsString) -> Option<ShaderParamValue> {
+        self.get_annotation_by_name(name)?.default_value()
+    }
+}
+
+/// Iterator over the annotations of a [`GraphicsEffectParam`], created by
+/// [`GraphicsEffectParam::annotations`].
+pub struct GraphicsEffectAnnotations<'a> {
+    param: &'a GraphicsEffectParam,
+    index: usize,
+    count: usize,
+}
+
+impl Iterator for GraphicsEffectAnnotations<'_> {
+    type Item = GraphicsEffectParam;
+
+    fn next(&mut self) -> Option<GraphicsEffectParam> {
+        while self.index < self.count {
+            let raw = unsafe { gs_param_get_annotation_by_idx(self.param.raw, self.index) };
+            self.index += 1;
+            if !raw.is_null() {
+                return Some(unsafe { GraphicsEffectParam::from_raw(raw) });
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.count - self.index))
+    }
+}
+
+/// A value read out of an effect file, either the default value of a
+/// parameter or the value of an annotation.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ShaderParamValue {
+    Bool(bool),
+    Float(f32),
+    Int(i32),
+    String(String),
+    Vec2([f32; 2]),
+    Vec3([f32; 3]),
+    Vec4([f32; 4]),
+    Int2([i32; 2]),
+    Int3([i32; 3]),
+    Int4([i32; 4]),
+    Mat4([f32; 16]),
+}
+
+impl ShaderParamValue {
+    fn from_bytes(shader_type: ShaderParamType, bytes: &[u8]) -> Option<Self> {
+        // libobs stores exactly one value of the parameter's type, anything
+        // else means the type and the data don't match
+        fn floats<const N: usize>(bytes: &[u8]) -> Option<[f32; N]> {
+            if bytes.len() != N * 4 {
+                return None;
+            }
+            let mut out = [0.; N];
+            for (value, chunk) in out.iter_mut().zip(bytes.chunks_exact(4)) {
+                *value = f32::from_ne_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+            }
+            Some(out)
+        }
+
+        fn ints<const N: usize>(bytes: &[u8]) -> Option<[i32; N]> {
+            if bytes.len() != N * 4 {
+                return None;
+            }
+            let mut out = [0; N];
+            for (value, chunk) in out.iter_mut().zip(bytes.chunks_exact(4)) {
+                *value = i32::from_ne_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+            }
+            Some(out)
+        }
+
+        if bytes.is_empty() && shader_type != ShaderParamType::String {
+            return None;
+        }
+
+        Some(match shader_type {
+            ShaderParamType::Bool => Self::Bool(bytes.iter().any(|b| *b != 0)),
+            ShaderParamType::Float => Self::Float(floats::<1>(bytes)?[0]),
+            ShaderParamType::Int => Self::Int(ints::<1>(bytes)?[0]),
+            ShaderParamType::String => {
+                let end = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+                Self::String(String::from_utf8_lossy(&bytes[..end]).into_owned())
+            }
+            ShaderParamType::Vec2 => Self::Vec2(floats(bytes)?),
+            ShaderParamType::Vec3 => Self::Vec3(floats(bytes)?),
+            ShaderParamType::Vec4 => Self::Vec4(floats(bytes)?),
+            ShaderParamType::Int2 => Self::Int2(ints(bytes)?),
+            ShaderParamType::Int3 => Self::Int3(ints(bytes)?),
+            ShaderParamType::Int4 => Self::Int4(ints(bytes)?),
+            ShaderParamType::Mat4 => Self::Mat4(floats(bytes)?),
+            ShaderParamType::Texture | ShaderParamType::Unknown => return None,
+        })
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::Bool(value) => Some(*value),
+            Self::Int(value) => Some(*value != 0),
+            _ => None,
+        }
+    }
+
+    /// Numeric values are implicitly casted.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::Float(value) => Some(*value as f64),
+            Self::Int(value) => Some(*value as f64),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(value) => Some(value),
+            _ => None,
+        }
+    }
 }
 
 macro_rules! impl_graphics_effects {
@@ -193,6 +685,9 @@ macro_rules! impl_graphics_effects {
 
 impl_graphics_effects! {
     Vec2
+    Vec3
+    Vec4
+    Mat4
     Texture
 }
 
@@ -204,6 +699,35 @@ impl GraphicsEffectVec2Param {
     }
 }
 
+impl GraphicsEffectVec3Param {
+    pub fn set_vec3(&mut self, _context: &GraphicsEffectContext, value: &Vec3) {
+        unsafe {
+            gs_effect_set_vec3(self.effect.raw, &value.raw);
+        }
+    }
+}
+
+impl GraphicsEffectVec4Param {
+    pub fn set_vec4(&mut self, _context: &GraphicsEffectContext, value: &Vec4) {
+        unsafe {
+            gs_effect_set_vec4(self.effect.raw, &value.raw);
+        }
+    }
+
+    /// Set a `float4` color uniform.
+    pub fn set_color(&mut self, context: &GraphicsEffectContext, value: display::ColorF32) {
+        self.set_vec4(context, &value.as_vec4());
+    }
+}
+
+impl GraphicsEffectMat4Param {
+    pub fn set_matrix4(&mut self, _context: &GraphicsEffectContext, value: &Matrix4) {
+        unsafe {
+            gs_effect_set_matrix4(self.effect.raw, value.as_ptr());
+        }
+    }
+}
+
 impl GraphicsEffectTextureParam {
     pub fn set_next_sampler(
         &mut self,
@@ -339,6 +863,47 @@ native_enum!(GraphicsColorFormat, gs_color_format {
     R8G8 => GS_R8G8,
 });
 
+impl GraphicsColorFormat {
+    /// Size of a single pixel in bytes, or `None` for block compressed and
+    /// unknown formats.
+    pub fn bytes_per_pixel(&self) -> Option<usize> {
+        match self {
+            Self::A8 | Self::R8 => Some(1),
+            Self::R16 | Self::R16F | Self::R8G8 => Some(2),
+            Self::RGBA | Self::BGRX | Self::BGRA | Self::R10G10B10A2 => Some(4),
+            Self::R32F | Self::RG16F => Some(4),
+            Self::RGBA16 | Self::RGBA16F | Self::RG32F => Some(8),
+            Self::RGBA32F => Some(16),
+            Self::DXT1 | Self::DXT3 | Self::DXT5 | Self::UNKNOWN => None,
+        }
+    }
+}
+
+native_enum!(GraphicsColorSpace, gs_color_space {
+    Srgb => GS_CS_SRGB,
+    Srgb16F => GS_CS_SRGB_16F,
+    Rec709Extended => GS_CS_709_EXTENDED,
+    Rec709ScRgb => GS_CS_709_SCRGB,
+});
+
+impl GraphicsColorSpace {
+    /// The texture format OBS uses for render targets in this color space,
+    /// matching `gs_get_format_from_space`.
+    pub fn format(&self) -> GraphicsColorFormat {
+        match self {
+            Self::Srgb => GraphicsColorFormat::RGBA,
+            Self::Srgb16F | Self::Rec709Extended | Self::Rec709ScRgb => {
+                GraphicsColorFormat::RGBA16F
+            }
+        }
+    }
+
+    /// Whether the color space can hold values outside of the SDR range.
+    pub fn is_hdr(&self) -> bool {
+        matches!(self, Self::Rec709Extended | Self::Rec709ScRgb)
+    }
+}
+
 native_enum!(GraphicsAllowDirectRendering, obs_allow_direct_render {
     NoDirectRendering => OBS_NO_DIRECT_RENDERING,
     AllowDirectRendering => OBS_ALLOW_DIRECT_RENDERING,
@@ -347,7 +912,8 @@ native_enum!(GraphicsAllowDirectRendering, obs_allow_direct_render {
 macro_rules! vector_impls {
     ($($rust_name: ident, $name:ident => $($component:ident)*,)*) => (
         $(
-        #[derive(Clone)]
+        #[derive(Clone, Copy)]
+        #[repr(transparent)]
         pub struct $rust_name {
             raw: $name,
         }
@@ -374,21 +940,25 @@ macro_rules! vector_impls {
             }
 
             #[inline]
+            #[deprecated(note = "use `+=`, `a.add(b)` is ambiguous with `std::ops`")]
             pub fn add(&mut self, input: &$rust_name) {
                 self.set($(self.$component() + input.$component(),)*);
             }
 
             #[inline]
+            #[deprecated(note = "use `-=`, `a.sub(b)` is ambiguous with `std::ops`")]
             pub fn sub(&mut self, input: &$rust_name) {
                 self.set($(self.$component() - input.$component(),)*);
             }
 
             #[inline]
+            #[deprecated(note = "use `*=`, `a.mul(b)` is ambiguous with `std::ops`")]
             pub fn mul(&mut self, input: &$rust_name) {
                 self.set($(self.$component() * input.$component(),)*);
             }
 
             #[inline]
+            #[deprecated(note = "use `/=`, `a.div(b)` is ambiguous with `std::ops`")]
             pub fn div(&mut self, input: &$rust_name) {
                 self.set($(self.$component() / input.$component(),)*);
             }
@@ -414,12 +984,13 @@ macro_rules! vector_impls {
             }
 
             #[inline]
+            #[deprecated(note = "use `-v`, `v.neg()` is ambiguous with `std::ops`")]
             pub fn neg(&mut self) {
                 self.set($(-self.$component(),)*);
             }
 
             #[inline]
-            pub fn dot(&mut self, input: &$rust_name) -> f32 {
+            pub fn dot(&self, input: &$rust_name) -> f32 {
                 $(
                     self.$component() * input.$component() +
                 )* 0.
@@ -427,7 +998,35 @@ macro_rules! vector_impls {
 
             #[inline]
             pub fn len(&mut self) -> f32 {
-                ($( self.$component() * self.$component() + )* 0.).sqrt()
+                self.length()
+            }
+
+            #[inline]
+            pub fn length_squared(&self) -> f32 {
+                $( self.$component() * self.$component() + )* 0.
+            }
+
+            #[inline]
+            pub fn length(&self) -> f32 {
+                self.length_squared().sqrt()
+            }
+
+            /// Returns a vector in the same direction with a length of one, or
+            /// a zero vector if the length is zero.
+            #[inline]
+            pub fn normalize(&self) -> Self {
+                let length = self.length();
+                if length > 0. {
+                    *self / length
+                } else {
+                    Self::default()
+                }
+            }
+
+            /// Linear interpolation between `self` and `to`.
+            #[inline]
+            pub fn lerp(&self, to: &$rust_name, t: f32) -> Self {
+                *self + (*to - *self) * t
             }
 
             #[inline]
@@ -503,6 +1102,120 @@ macro_rules! vector_impls {
                 Self::new($( $component, )*)
             }
         }
+
+        impl std::fmt::Debug for $rust_name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.debug_struct(stringify!($rust_name))
+                    $( .field(stringify!($component), &self.$component()) )*
+                    .finish()
+            }
+        }
+
+        impl PartialEq for $rust_name {
+            fn eq(&self, other: &Self) -> bool {
+                $( self.$component() == other.$component() && )* true
+            }
+        }
+
+        impl std::ops::Add for $rust_name {
+            type Output = Self;
+
+            fn add(self, rhs: Self) -> Self {
+                Self::new($( self.$component() + rhs.$component(), )*)
+            }
+        }
+
+        impl std::ops::Sub for $rust_name {
+            type Output = Self;
+
+            fn sub(self, rhs: Self) -> Self {
+                Self::new($( self.$component() - rhs.$component(), )*)
+            }
+        }
+
+        impl std::ops::Mul for $rust_name {
+            type Output = Self;
+
+            fn mul(self, rhs: Self) -> Self {
+                Self::new($( self.$component() * rhs.$component(), )*)
+            }
+        }
+
+        impl std::ops::Div for $rust_name {
+            type Output = Self;
+
+            fn div(self, rhs: Self) -> Self {
+                Self::new($( self.$component() / rhs.$component(), )*)
+            }
+        }
+
+        impl std::ops::Mul<f32> for $rust_name {
+            type Output = Self;
+
+            fn mul(self, rhs: f32) -> Self {
+                Self::new($( self.$component() * rhs, )*)
+            }
+        }
+
+        impl std::ops::Mul<$rust_name> for f32 {
+            type Output = $rust_name;
+
+            fn mul(self, rhs: $rust_name) -> $rust_name {
+                rhs * self
+            }
+        }
+
+        impl std::ops::Div<f32> for $rust_name {
+            type Output = Self;
+
+            fn div(self, rhs: f32) -> Self {
+                Self::new($( self.$component() / rhs, )*)
+            }
+        }
+
+        impl std::ops::Neg for $rust_name {
+            type Output = Self;
+
+            fn neg(self) -> Self {
+                Self::new($( -self.$component(), )*)
+            }
+        }
+
+        impl std::ops::AddAssign for $rust_name {
+            fn add_assign(&mut self, rhs: Self) {
+                *self = *self + rhs;
+            }
+        }
+
+        impl std::ops::SubAssign for $rust_name {
+            fn sub_assign(&mut self, rhs: Self) {
+                *self = *self - rhs;
+            }
+        }
+
+        impl std::ops::MulAssign for $rust_name {
+            fn mul_assign(&mut self, rhs: Self) {
+                *self = *self * rhs;
+            }
+        }
+
+        impl std::ops::DivAssign for $rust_name {
+            fn div_assign(&mut self, rhs: Self) {
+                *self = *self / rhs;
+            }
+        }
+
+        impl std::ops::MulAssign<f32> for $rust_name {
+            fn mul_assign(&mut self, rhs: f32) {
+                *self = *self * rhs;
+            }
+        }
+
+        impl std::ops::DivAssign<f32> for $rust_name {
+            fn div_assign(&mut self, rhs: f32) {
+                *self = *self / rhs;
+            }
+        }
         )*
     );
 }
@@ -513,6 +1226,25 @@ vector_impls! {
     Vec4, vec4 => x y z w,
 }
 
+impl Vec3 {
+    #[inline]
+    pub fn cross(&self, input: &Vec3) -> Vec3 {
+        Vec3::new(
+            self.y() * input.z() - self.z() * input.y(),
+            self.z() * input.x() - self.x() * input.z(),
+            self.x() * input.y() - self.y() * input.x(),
+        )
+    }
+}
+
+impl Vec4 {
+    /// Extend a [`Vec3`] with a `w` component.
+    #[inline]
+    pub fn from_vec3(v: &Vec3, w: f32) -> Vec4 {
+        Vec4::new(v.x(), v.y(), v.z(), w)
+    }
+}
+
 /// Wrapper around [`gs_texture_t`](https://obsproject.com/docs/reference-libobs-graphics-graphics.html#c.gs_texture_t)
 pub struct GraphicsTexture {
     raw: *mut gs_texture_t,
@@ -526,6 +1258,31 @@ impl GraphicsTexture {
         Self { raw }
     }
 
+    /// Create a static texture from tightly packed RGBA8 pixel data, e.g. an
+    /// image decoded in Rust. Returns `None` if `data` is not exactly
+    /// `width * height * 4` bytes long or the texture could not be created.
+    pub fn from_rgba_bytes(width: u32, height: u32, data: &[u8]) -> Option<Self> {
+        if data.len() != width as usize * height as usize * 4 {
+            return None;
+        }
+        let mut planes = [data.as_ptr()];
+        let raw = GraphicsGuard::with_enter(|| unsafe {
+            gs_texture_create(
+                width,
+                height,
+                GraphicsColorFormat::RGBA.as_raw(),
+                1,
+                planes.as_mut_ptr(),
+                0,
+            )
+        });
+        if raw.is_null() {
+            None
+        } else {
+            Some(Self { raw })
+        }
+    }
+
     #[inline]
     pub fn height(&self) -> u32 {
         GraphicsGuard::with_enter(|| unsafe { gs_texture_get_height(self.raw) })
@@ -548,6 +1305,11 @@ impl GraphicsTexture {
         }
     }
 
+    pub fn format(&self) -> GraphicsColorFormat {
+        let raw = GraphicsGuard::with_enter(|| unsafe { gs_texture_get_color_format(self.raw) });
+        GraphicsColorFormat::from_raw(raw).unwrap_or(GraphicsColorFormat::UNKNOWN)
+    }
+
     #[inline]
     pub fn map(&mut self) -> Result<MappedTexture> {
         MappedTexture::new(self)
@@ -566,6 +1328,37 @@ impl Drop for GraphicsTexture {
     }
 }
 
+/// A [`GraphicsTexture`] which is owned by something else (e.g. a
+/// [`GraphicsTexRender`]) and is not destroyed when dropped.
+pub struct GraphicsTextureRef<'a> {
+    texture: ManuallyDrop<GraphicsTexture>,
+    _owner: PhantomData<&'a ()>,
+}
+
+impl GraphicsTextureRef<'_> {
+    /// # Safety
+    /// The pointer must be a valid texture that outlives the returned
+    /// reference.
+    pub(crate) unsafe fn from_raw(raw: *mut gs_texture_t) -> Option<Self> {
+        if raw.is_null() {
+            None
+        } else {
+            Some(Self {
+                texture: ManuallyDrop::new(GraphicsTexture { raw }),
+                _owner: PhantomData,
+            })
+        }
+    }
+}
+
+impl std::ops::Deref for GraphicsTextureRef<'_> {
+    type Target = GraphicsTexture;
+
+    fn deref(&self) -> &GraphicsTexture {
+        &self.texture
+    }
+}
+
 /// Represents a mapped texture blob from [`GraphicsTexture`].
 pub struct MappedTexture<'tex> {
     tex: &'tex mut GraphicsTexture,
@@ -635,3 +1428,143 @@ impl Drop for MappedTexture<'_> {
         });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bytes_of(values: &[f32]) -> Vec<u8> {
+        values.iter().flat_map(|v| v.to_ne_bytes()).collect()
+    }
+
+    fn bytes_of_ints(values: &[i32]) -> Vec<u8> {
+        values.iter().flat_map(|v| v.to_ne_bytes()).collect()
+    }
+
+    #[test]
+    fn diagnostic_parse() {
+        assert_eq!(
+            ShaderDiagnostic::parse("crop_filter.effect (12, 5): Unexpected token"),
+            Some(ShaderDiagnostic {
+                file: "crop_filter.effect".into(),
+                line: 12,
+                column: 5,
+                message: "Unexpected token".into(),
+            })
+        );
+        // only the last " (" starts the position
+        assert_eq!(
+            ShaderDiagnostic::parse("/effects/my (copy)/blur.effect (3, 14): expected ';'"),
+            Some(ShaderDiagnostic {
+                file: "/effects/my (copy)/blur.effect".into(),
+                line: 3,
+                column: 14,
+                message: "expected ';'".into(),
+            })
+        );
+        // only the first "): " ends the position
+        assert_eq!(
+            ShaderDiagnostic::parse("blur.effect (1, 2): call to f(x): no match"),
+            Some(ShaderDiagnostic {
+                file: "blur.effect".into(),
+                line: 1,
+                column: 2,
+                message: "call to f(x): no match".into(),
+            })
+        );
+        assert_eq!(ShaderDiagnostic::parse("Effect compilation failed"), None);
+        assert_eq!(ShaderDiagnostic::parse("blur.effect (a, b): message"), None);
+    }
+
+    #[test]
+    fn compile_output_parse() {
+        let raw = "Device error\n\
+                   blur.effect (1, 2): first\n\
+                   not a diagnostic\n\
+                   blur.effect (3, 4): second"
+            .to_string();
+        let output = ShaderCompileOutput::parse(raw.clone());
+        assert_eq!(output.raw, raw);
+        let lines: Vec<_> = output
+            .diagnostics
+            .iter()
+            .map(|d| (d.line, d.column, d.message.as_str()))
+            .collect();
+        assert_eq!(lines, [(1, 2, "first"), (3, 4, "second")]);
+        assert_eq!(
+            output.diagnostics[1].to_string(),
+            "blur.effect (3, 4): second"
+        );
+
+        let empty = ShaderCompileOutput::parse(String::new());
+        assert!(empty.diagnostics.is_empty());
+        assert_eq!(empty.to_string(), "no compiler output");
+    }
+
+    #[test]
+    fn param_value_from_bytes() {
+        use ShaderParamType as T;
+        use ShaderParamValue as V;
+
+        let mat: Vec<f32> = (0..16).map(|i| i as f32).collect();
+        let mut mat4 = [0.; 16];
+        mat4.copy_from_slice(&mat);
+
+        let cases: Vec<(ShaderParamType, Vec<u8>, Option<ShaderParamValue>)> = vec![
+            (T::Bool, vec![1, 0, 0, 0], Some(V::Bool(true))),
+            (T::Bool, vec![0, 0, 0, 0], Some(V::Bool(false))),
+            (T::Bool, vec![], None),
+            (T::Float, bytes_of(&[1.5]), Some(V::Float(1.5))),
+            (T::Float, vec![0, 0], None),
+            (T::Float, bytes_of(&[1.5, 2.]), None),
+            (T::Int, bytes_of_ints(&[-7]), Some(V::Int(-7))),
+            (T::Int, vec![], None),
+            (T::Vec2, bytes_of(&[1., 2.]), Some(V::Vec2([1., 2.]))),
+            (
+                T::Vec3,
+                bytes_of(&[1., 2., 3.]),
+                Some(V::Vec3([1., 2., 3.])),
+            ),
+            (T::Vec3, bytes_of(&[1., 2.]), None),
+            (
+                T::Vec4,
+                bytes_of(&[1., 2., 3., 4.]),
+                Some(V::Vec4([1., 2., 3., 4.])),
+            ),
+            (T::Vec4, bytes_of(&[1., 2., 3., 4., 5.]), None),
+            (T::Int2, bytes_of_ints(&[1, 2]), Some(V::Int2([1, 2]))),
+            (T::Int3, bytes_of_ints(&[1, 2, 3]), Some(V::Int3([1, 2, 3]))),
+            (
+                T::Int4,
+                bytes_of_ints(&[1, 2, 3, 4]),
+                Some(V::Int4([1, 2, 3, 4])),
+            ),
+            (T::Int4, bytes_of_ints(&[1, 2, 3]), None),
+            (T::Mat4, bytes_of(&mat), Some(V::Mat4(mat4))),
+            (T::Mat4, bytes_of(&mat[..15]), None),
+            (
+                T::String,
+                b"hello\0junk".to_vec(),
+                Some(V::String("hello".into())),
+            ),
+            (
+                T::String,
+                b"no nul".to_vec(),
+                Some(V::String("no nul".into())),
+            ),
+            (T::String, vec![], Some(V::String(String::new()))),
+            (T::Texture, vec![0; 8], None),
+            (T::Unknown, vec![0; 4], None),
+        ];
+
+        for (shader_type, bytes, expected) in cases {
+            assert_eq!(
+                ShaderParamValue::from_bytes(shader_type, &bytes),
+                expected,
+                "{:?} {:?}",
+                shader_type,
+                bytes
+            );
+        }
+    }
+}
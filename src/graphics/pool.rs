@@ -0,0 +1,135 @@
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    rc::{Rc, Weak},
+};
+
+use super::{GraphicsColorFormat, GraphicsTexture};
+
+/// Size and format of a pooled texture.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TextureKey {
+    pub width: u32,
+    pub height: u32,
+    pub format: GraphicsColorFormat,
+}
+
+#[derive(Default)]
+struct PoolInner {
+    free: HashMap<TextureKey, Vec<GraphicsTexture>>,
+    used: HashSet<TextureKey>,
+}
+
+/// A pool of [`GraphicsTexture`]s which recycles textures of the same size
+/// and format instead of allocating new ones every frame.
+///
+/// Textures are leased with [`get`](Self::get) and go back into the pool when
+/// the [`PooledTexture`] is dropped. Cloning the pool gives another handle to
+/// the same textures.
+///
+/// Textures for sizes that stop being requested (e.g. after the source was
+/// resized) stay in the pool until [`trim`](Self::trim) or
+/// [`clear`](Self::clear) is called, so call `trim` regularly, such as once per
+/// `video_tick`.
+#[derive(Clone, Default)]
+pub struct TexturePool {
+    inner: Rc<RefCell<PoolInner>>,
+}
+
+impl TexturePool {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Lease a texture, reusing a free one with the same size and format if
+    /// there is one. The contents of a reused texture are whatever was last
+    /// written to it.
+    pub fn get(&self, width: u32, height: u32, format: GraphicsColorFormat) -> PooledTexture {
+        let key = TextureKey {
+            width,
+            height,
+            format,
+        };
+        let texture = {
+            let mut inner = self.inner.borrow_mut();
+            inner.used.insert(key);
+            inner.free.get_mut(&key).and_then(|textures| textures.pop())
+        };
+        let texture = texture.unwrap_or_else(|| GraphicsTexture::new(width, height, format));
+        PooledTexture {
+            texture: Some(texture),
+            key,
+            pool: Rc::downgrade(&self.inner),
+        }
+    }
+
+    /// Destroy free textures whose size and format weren't requested since
+    /// the last call to `trim`.
+    pub fn trim(&self) {
+        let mut inner = self.inner.borrow_mut();
+        let used = std::mem::take(&mut inner.used);
+        inner.free.retain(|key, _| used.contains(key));
+    }
+
+    /// Destroy all free textures. Leased textures are unaffected.
+    pub fn clear(&self) {
+        let mut inner = self.inner.borrow_mut();
+        inner.free.clear();
+        inner.used.clear();
+    }
+
+    /// Number of textures waiting to be reused.
+    pub fn free_count(&self) -> usize {
+        self.inner.borrow().free.values().map(Vec::len).sum()
+    }
+}
+
+/// A texture leased from a [`TexturePool`], returned to the pool when
+/// dropped. If the pool was dropped first, the texture is destroyed instead.
+pub struct PooledTexture {
+    texture: Option<GraphicsTexture>,
+    key: TextureKey,
+    pool: Weak<RefCell<PoolInner>>,
+}
+
+impl PooledTexture {
+    pub fn key(&self) -> TextureKey {
+        self.key
+    }
+
+    /// Take the texture out of the pool, so it is destroyed normally instead
+    /// of being recycled.
+    pub fn detach(mut self) -> GraphicsTexture {
+        self.texture.take().expect("texture is only taken on drop")
+    }
+}
+
+impl std::ops::Deref for PooledTexture {
+    type Target = GraphicsTexture;
+
+    fn deref(&self) -> &GraphicsTexture {
+        self.texture
+            .as_ref()
+            .expect("texture is only taken on drop")
+    }
+}
+
+impl std::ops::DerefMut for PooledTexture {
+    fn deref_mut(&mut self) -> &mut GraphicsTexture {
+        self.texture
+            .as_mut()
+            .expect("texture is only taken on drop")
+    }
+}
+
+impl Drop for PooledTexture {
+    fn drop(&mut self) {
+        if let (Some(texture), Some(pool)) = (self.texture.take(), self.pool.upgrade()) {
+            pool.borrow_mut()
+                .free
+                .entry(self.key)
+                .or_default()
+                .push(texture);
+        }
+    }
+}
@@ -0,0 +1,121 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use super::GraphicsEffect;
+use crate::string::{ObsString, TryIntoObsString};
+
+/// A [`GraphicsEffect`] loaded from a file which is recompiled whenever the
+/// file changes, for iterating on shaders without restarting OBS.
+///
+/// Call [`video_tick`](Self::video_tick) from the source's `video_tick`. If
+/// the new version of the file fails to compile, the error is logged and the
+/// previous effect is kept.
+///
+/// OBS keeps every compiled effect alive until the graphics subsystem shuts
+/// down, so this is meant for development builds rather than release. The
+/// file is only watched in debug builds unless enabled with
+/// [`set_watching`](Self::set_watching).
+pub struct ReloadableEffect {
+    path: PathBuf,
+    effect: Option<GraphicsEffect>,
+    modified: Option<SystemTime>,
+    last_error: Option<String>,
+    watching: bool,
+}
+
+impl ReloadableEffect {
+    /// Load the effect at `path`. Check [`effect`](Self::effect) or
+    /// [`last_error`](Self::last_error) to see whether it compiled.
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        let mut effect = Self {
+            path: path.into(),
+            effect: None,
+            modified: None,
+            last_error: None,
+            watching: cfg!(debug_assertions),
+        };
+        effect.reload();
+        effect
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The most recent effect which compiled successfully.
+    pub fn effect(&self) -> Option<&GraphicsEffect> {
+        self.effect.as_ref()
+    }
+
+    pub fn effect_mut(&mut self) -> Option<&mut GraphicsEffect> {
+        self.effect.as_mut()
+    }
+
+    /// The error from the last reload, if it failed.
+    pub fn last_error(&self) -> Option<&str> {
+        self.last_error.as_deref()
+    }
+
+    /// Whether [`video_tick`](Self::video_tick) checks the file for changes.
+    pub fn is_watching(&self) -> bool {
+        self.watching
+    }
+
+    pub fn set_watching(&mut self, watching: bool) {
+        self.watching = watching;
+    }
+
+    /// Recompile the effect if the file was modified since it was last
+    /// loaded. Returns `true` if a new effect was loaded, in which case any
+    /// parameters looked up from the old effect must be fetched again.
+    ///
+    /// Does nothing unless [`is_watching`](Self::is_watching).
+    pub fn video_tick(&mut self) -> bool {
+        if !self.watching {
+            return false;
+        }
+        let modified = fs::metadata(&self.path).and_then(|m| m.modified()).ok();
+        if modified.is_some() && modified != self.modified {
+            self.reload()
+        } else {
+            false
+        }
+    }
+
+    /// Recompile the effect from the file, returning `true` on success.
+    pub fn reload(&mut self) -> bool {
+        self.modified = fs::metadata(&self.path).and_then(|m| m.modified()).ok();
+        match self.compile() {
+            Ok(effect) => {
+                self.effect = Some(effect);
+                self.last_error = None;
+                true
+            }
+            Err(message) => {
+                log::warn!(
+                    "Failed to compile effect {}: {}",
+                    self.path.display(),
+                    message
+                );
+                self.last_error = Some(message);
+                false
+            }
+        }
+    }
+
+    fn compile(&self) -> Result<GraphicsEffect, String> {
+        // Read the file ourselves, `gs_effect_create_from_file` would return
+        // the cached effect instead of recompiling it.
+        let source = fs::read_to_string(&self.path).map_err(|e| e.to_string())?;
+        let source = ObsString::from(source);
+        let name = self
+            .path
+            .as_path()
+            .try_into_obs_string()
+            .map_err(|e| e.to_string())?;
+        GraphicsEffect::from_effect_string(source, name).map_err(|e| e.to_string())
+    }
+}
@@ -0,0 +1,312 @@
+use obs_sys::{
+    gs_stage_texture, gs_stagesurf_t, gs_stagesurface_create, gs_stagesurface_destroy,
+    gs_stagesurface_get_color_format, gs_stagesurface_get_height, gs_stagesurface_get_width,
+    gs_stagesurface_map, gs_stagesurface_unmap,
+};
+use std::{ptr, slice};
+
+use super::{GraphicsColorFormat, GraphicsGuard, GraphicsTexture};
+use crate::{source::VideoRenderContext, Error, Result};
+
+/// Wrapper around [`gs_stagesurf_t`](https://obsproject.com/docs/reference-libobs-graphics-graphics.html#c.gs_stagesurf_t),
+/// used to copy the contents of a texture from the GPU to the CPU.
+pub struct GraphicsStageSurface {
+    raw: *mut gs_stagesurf_t,
+}
+
+impl GraphicsStageSurface {
+    pub fn new(width: u32, height: u32, format: GraphicsColorFormat) -> Self {
+        let raw = GraphicsGuard::with_enter(|| unsafe {
+            gs_stagesurface_create(width, height, format.as_raw())
+        });
+        Self { raw }
+    }
+
+    #[inline]
+    pub fn width(&self) -> u32 {
+        GraphicsGuard::with_enter(|| unsafe { gs_stagesurface_get_width(self.raw) })
+    }
+
+    #[inline]
+    pub fn height(&self) -> u32 {
+        GraphicsGuard::with_enter(|| unsafe { gs_stagesurface_get_height(self.raw) })
+    }
+
+    pub fn format(&self) -> GraphicsColorFormat {
+        let raw =
+            GraphicsGuard::with_enter(|| unsafe { gs_stagesurface_get_color_format(self.raw) });
+        GraphicsColorFormat::from_raw(raw).unwrap_or(GraphicsColorFormat::UNKNOWN)
+    }
+
+    /// Queue a copy of `texture` into this surface. The texture must have the
+    /// same size and format as the surface.
+    ///
+    /// The copy happens asynchronously on the GPU, mapping straight after
+    /// staging will stall until it is done. See [`StagedReadback`] for a way
+    /// to avoid that.
+    pub fn stage(&mut self, _render: &mut VideoRenderContext, texture: &GraphicsTexture) {
+        unsafe {
+            gs_stage_texture(self.raw, texture.as_ptr());
+        }
+    }
+
+    #[inline]
+    pub fn map(&mut self) -> Result<MappedStageSurface<'_>> {
+        MappedStageSurface::new(self)
+    }
+
+    pub fn as_ptr(&self) -> *mut gs_stagesurf_t {
+        self.raw
+    }
+}
+
+impl Drop for GraphicsStageSurface {
+    fn drop(&mut self) {
+        GraphicsGuard::with_enter(|| unsafe {
+            gs_stagesurface_destroy(self.raw);
+        });
+    }
+}
+
+/// Represents a mapped blob from [`GraphicsStageSurface`].
+///
+/// Rows are `linesize` bytes apart, which can be larger than
+/// `width * bytes_per_pixel`, so prefer [`row`](Self::row) or
+/// [`rows`](Self::rows) over indexing the blob directly.
+pub struct MappedStageSurface<'surf> {
+    surf: &'surf mut GraphicsStageSurface,
+    ptr: *mut u8,
+    linesize: usize,
+    width: u32,
+    height: u32,
+    format: GraphicsColorFormat,
+}
+
+impl<'surf> MappedStageSurface<'surf> {
+    fn new(surf: &'surf mut GraphicsStageSurface) -> Result<Self> {
+        let mut ptr: *mut u8 = ptr::null_mut();
+        let mut linesize = 0u32;
+        let map_result = GraphicsGuard::with_enter(|| unsafe {
+            gs_stagesurface_map(surf.as_ptr(), &mut ptr, &mut linesize)
+        });
+        if !map_result {
+            return Err(Error::ObsError(-1));
+        }
+        let width = surf.width();
+        let height = surf.height();
+        let format = surf.format();
+        Ok(Self {
+            surf,
+            ptr,
+            linesize: linesize as usize,
+            width,
+            height,
+            format,
+        })
+    }
+
+    #[inline]
+    pub fn as_ptr(&self) -> *const u8 {
+        self.ptr
+    }
+
+    #[inline]
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    #[inline]
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    #[inline]
+    pub fn format(&self) -> GraphicsColorFormat {
+        self.format
+    }
+
+    /// Number of bytes between the start of two rows.
+    #[inline]
+    pub fn linesize(&self) -> usize {
+        self.linesize
+    }
+
+    /// Number of bytes of pixel data in a row, without padding.
+    pub fn row_len(&self) -> usize {
+        self.format
+            .bytes_per_pixel()
+            .map(|bpp| bpp * self.width as usize)
+            .unwrap_or(self.linesize)
+            .min(self.linesize)
+    }
+
+    /// The pixel data of row `y`, without padding.
+    pub fn row(&self, y: u32) -> Option<&[u8]> {
+        if y >= self.height {
+            return None;
+        }
+        let start = y as usize * self.linesize;
+        Some(&self[start..start + self.row_len()])
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[u8]> + '_ {
+        (0..self.height).filter_map(move |y| self.row(y))
+    }
+
+    /// Copy the pixel data into a tightly packed buffer.
+    pub fn to_vec(&self) -> Vec<u8> {
+        let mut data = Vec::with_capacity(self.row_len() * self.height as usize);
+        for row in self.rows() {
+            data.extend_from_slice(row);
+        }
+        data
+    }
+}
+
+impl std::ops::Deref for MappedStageSurface<'_> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        unsafe { slice::from_raw_parts(self.as_ptr(), self.linesize * self.height as usize) }
+    }
+}
+
+impl std::fmt::Debug for MappedStageSurface<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MappedStageSurface")
+            .field("width", &self.width)
+            .field("height", &self.height)
+            .field("linesize", &self.linesize)
+            .field("format", &self.format)
+            .finish()
+    }
+}
+
+impl Drop for MappedStageSurface<'_> {
+    fn drop(&mut self) {
+        GraphicsGuard::with_enter(|| unsafe {
+            gs_stagesurface_unmap(self.surf.as_ptr());
+        });
+    }
+}
+
+/// Which of the two surfaces of a [`StagedReadback`] to write and read.
+#[derive(Debug, Default)]
+struct ReadbackSlots {
+    staged: [bool; 2],
+    current: usize,
+}
+
+impl ReadbackSlots {
+    /// The slot to stage into this frame. It holds the oldest copy, which
+    /// [`readable`](Self::readable) returns until then.
+    fn stage(&mut self) -> usize {
+        let slot = self.current;
+        self.staged[slot] = true;
+        self.current = 1 - slot;
+        slot
+    }
+
+    /// The slot staged on the frame before the last call to
+    /// [`stage`](Self::stage), whose copy should be done by now.
+    fn readable(&self) -> Option<usize> {
+        let slot = self.current;
+        self.staged[slot].then_some(slot)
+    }
+
+    fn clear(&mut self) {
+        self.staged = [false; 2];
+    }
+}
+
+/// Non-blocking readback of a texture that changes every frame.
+///
+/// Two stage surfaces are used in turn: each frame a texture is staged into
+/// one while the other, staged on the frame before, is read. This trades a
+/// frame of latency for not stalling the render thread while waiting on the
+/// GPU.
+#[derive(Default)]
+pub struct StagedReadback {
+    surfaces: [Option<GraphicsStageSurface>; 2],
+    slots: ReadbackSlots,
+}
+
+impl StagedReadback {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue a copy of `texture`, recreating the surfaces if its size or
+    /// format changed.
+    pub fn stage(&mut self, render: &mut VideoRenderContext, texture: &GraphicsTexture) {
+        let width = texture.width();
+        let height = texture.height();
+        let format = texture.format();
+
+        let slot = self.slots.stage();
+        let surface = match &mut self.surfaces[slot] {
+            Some(surface)
+                if surface.width() == width
+                    && surface.height() == height
+                    && surface.format() == format =>
+            {
+                surface
+            }
+            surface => {
+                // the other surface holds a copy of the old size, drop it
+                self.slots.clear();
+                self.slots.staged[slot] = true;
+                surface.insert(GraphicsStageSurface::new(width, height, format))
+            }
+        };
+
+        surface.stage(render, texture);
+    }
+
+    /// Map the surface staged one frame before the last call to
+    /// [`stage`](Self::stage) and run `func` with its contents. Returns
+    /// `None` until two frames have been staged, or if mapping failed.
+    pub fn read<T, F: FnOnce(&MappedStageSurface) -> T>(&mut self, func: F) -> Option<T> {
+        let slot = self.slots.readable()?;
+        let surface = self.surfaces[slot].as_mut()?;
+        let mapped = surface.map().ok()?;
+        Some(func(&mapped))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_previous_frame() {
+        let mut slots = ReadbackSlots::default();
+        assert_eq!(slots.readable(), None);
+
+        // first frame: only the copy that was just queued exists
+        let first = slots.stage();
+        assert_eq!(slots.readable(), None);
+
+        // second frame: the first frame's copy is read, not the new one
+        let second = slots.stage();
+        assert_ne!(first, second);
+        assert_eq!(slots.readable(), Some(first));
+
+        let third = slots.stage();
+        assert_eq!(third, first);
+        assert_eq!(slots.readable(), Some(second));
+    }
+
+    #[test]
+    fn clear_waits_for_new_copies() {
+        let mut slots = ReadbackSlots::default();
+        slots.stage();
+        slots.stage();
+        slots.clear();
+        assert_eq!(slots.readable(), None);
+        slots.stage();
+        assert_eq!(slots.readable(), None);
+        slots.stage();
+        assert!(slots.readable().is_some());
+    }
+}
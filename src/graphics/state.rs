@@ -0,0 +1,317 @@
+use obs_sys::{
+    gs_blend_function, gs_blend_function_separate, gs_blend_op, gs_blend_op_type,
+    gs_blend_op_type_GS_BLEND_OP_ADD, gs_blend_op_type_GS_BLEND_OP_MAX,
+    gs_blend_op_type_GS_BLEND_OP_MIN, gs_blend_op_type_GS_BLEND_OP_REVERSE_SUBTRACT,
+    gs_blend_op_type_GS_BLEND_OP_SUBTRACT, gs_blend_state_pop, gs_blend_state_push, gs_blend_type,
+    gs_blend_type_GS_BLEND_DSTALPHA, gs_blend_type_GS_BLEND_DSTCOLOR,
+    gs_blend_type_GS_BLEND_INVDSTALPHA, gs_blend_type_GS_BLEND_INVDSTCOLOR,
+    gs_blend_type_GS_BLEND_INVSRCALPHA, gs_blend_type_GS_BLEND_INVSRCCOLOR,
+    gs_blend_type_GS_BLEND_ONE, gs_blend_type_GS_BLEND_SRCALPHA,
+    gs_blend_type_GS_BLEND_SRCALPHASAT, gs_blend_type_GS_BLEND_SRCCOLOR,
+    gs_blend_type_GS_BLEND_ZERO, gs_enable_blending, gs_enable_framebuffer_srgb,
+    gs_framebuffer_srgb_enabled, gs_get_viewport, gs_matrix_get, gs_matrix_identity, gs_matrix_mul,
+    gs_matrix_pop, gs_matrix_push, gs_matrix_rotaa4f, gs_matrix_scale3f, gs_matrix_set,
+    gs_matrix_translate3f, gs_matrix_transpose, gs_rect, gs_reset_blend_state, gs_set_linear_srgb,
+    gs_set_viewport, gs_viewport_pop, gs_viewport_push,
+};
+use std::{marker::PhantomData, os::raw::c_int};
+
+use super::{GraphicsEffectContext, Matrix4, Vec3};
+use crate::native_enum;
+
+native_enum!(GraphicsBlendType, gs_blend_type {
+    Zero => GS_BLEND_ZERO,
+    One => GS_BLEND_ONE,
+    SrcColor => GS_BLEND_SRCCOLOR,
+    InvSrcColor => GS_BLEND_INVSRCCOLOR,
+    SrcAlpha => GS_BLEND_SRCALPHA,
+    InvSrcAlpha => GS_BLEND_INVSRCALPHA,
+    DstColor => GS_BLEND_DSTCOLOR,
+    InvDstColor => GS_BLEND_INVDSTCOLOR,
+    DstAlpha => GS_BLEND_DSTALPHA,
+    InvDstAlpha => GS_BLEND_INVDSTALPHA,
+    SrcAlphaSat => GS_BLEND_SRCALPHASAT,
+});
+
+native_enum!(GraphicsBlendOp, gs_blend_op_type {
+    Add => GS_BLEND_OP_ADD,
+    Subtract => GS_BLEND_OP_SUBTRACT,
+    ReverseSubtract => GS_BLEND_OP_REVERSE_SUBTRACT,
+    Min => GS_BLEND_OP_MIN,
+    Max => GS_BLEND_OP_MAX,
+});
+
+/// Pushes the blend state on creation and pops it when dropped, so any
+/// changes made through the guard don't leak into the rest of OBS's
+/// rendering.
+pub struct BlendStateGuard<'a> {
+    _context: PhantomData<&'a GraphicsEffectContext>,
+}
+
+impl<'a> BlendStateGuard<'a> {
+    pub fn push(_context: &'a GraphicsEffectContext) -> Self {
+        unsafe {
+            gs_blend_state_push();
+        }
+        Self {
+            _context: PhantomData,
+        }
+    }
+
+    /// Reset blending to OBS's defaults (enabled, `SrcAlpha`/`InvSrcAlpha`).
+    pub fn reset(&mut self) -> &mut Self {
+        unsafe {
+            gs_reset_blend_state();
+        }
+        self
+    }
+
+    pub fn enable(&mut self, enable: bool) -> &mut Self {
+        unsafe {
+            gs_enable_blending(enable);
+        }
+        self
+    }
+
+    pub fn function(&mut self, src: GraphicsBlendType, dest: GraphicsBlendType) -> &mut Self {
+        unsafe {
+            gs_blend_function(src.as_raw(), dest.as_raw());
+        }
+        self
+    }
+
+    pub fn function_separate(
+        &mut self,
+        src_color: GraphicsBlendType,
+        dest_color: GraphicsBlendType,
+        src_alpha: GraphicsBlendType,
+        dest_alpha: GraphicsBlendType,
+    ) -> &mut Self {
+        unsafe {
+            gs_blend_function_separate(
+                src_color.as_raw(),
+                dest_color.as_raw(),
+                src_alpha.as_raw(),
+                dest_alpha.as_raw(),
+            );
+        }
+        self
+    }
+
+    pub fn op(&mut self, op: GraphicsBlendOp) -> &mut Self {
+        unsafe {
+            gs_blend_op(op.as_raw());
+        }
+        self
+    }
+}
+
+impl Drop for BlendStateGuard<'_> {
+    fn drop(&mut self) {
+        unsafe {
+            gs_blend_state_pop();
+        }
+    }
+}
+
+/// Pushes the current transform matrix on creation and pops it when dropped.
+pub struct MatrixGuard<'a> {
+    _context: PhantomData<&'a GraphicsEffectContext>,
+}
+
+impl<'a> MatrixGuard<'a> {
+    pub fn push(_context: &'a GraphicsEffectContext) -> Self {
+        unsafe {
+            gs_matrix_push();
+        }
+        Self {
+            _context: PhantomData,
+        }
+    }
+
+    pub fn identity(&mut self) -> &mut Self {
+        unsafe {
+            gs_matrix_identity();
+        }
+        self
+    }
+
+    /// Replace the current matrix.
+    pub fn set(&mut self, matrix: &Matrix4) -> &mut Self {
+        unsafe {
+            gs_matrix_set(matrix.as_ptr());
+        }
+        self
+    }
+
+    pub fn get(&self) -> Matrix4 {
+        let mut matrix = Matrix4::identity();
+        unsafe {
+            gs_matrix_get(matrix.as_mut_ptr());
+        }
+        matrix
+    }
+
+    /// Multiply the current matrix by `matrix`.
+    pub fn mul(&mut self, matrix: &Matrix4) -> &mut Self {
+        unsafe {
+            gs_matrix_mul(matrix.as_ptr());
+        }
+        self
+    }
+
+    pub fn transpose(&mut self) -> &mut Self {
+        unsafe {
+            gs_matrix_transpose();
+        }
+        self
+    }
+
+    pub fn translate(&mut self, offset: &Vec3) -> &mut Self {
+        self.translate3f(offset.x(), offset.y(), offset.z())
+    }
+
+    pub fn translate3f(&mut self, x: f32, y: f32, z: f32) -> &mut Self {
+        unsafe {
+            gs_matrix_translate3f(x, y, z);
+        }
+        self
+    }
+
+    pub fn scale(&mut self, scale: &Vec3) -> &mut Self {
+        self.scale3f(scale.x(), scale.y(), scale.z())
+    }
+
+    pub fn scale3f(&mut self, x: f32, y: f32, z: f32) -> &mut Self {
+        unsafe {
+            gs_matrix_scale3f(x, y, z);
+        }
+        self
+    }
+
+    /// Rotate by `angle` radians around the axis `(x, y, z)`.
+    pub fn rotate_axis_angle(&mut self, x: f32, y: f32, z: f32, angle: f32) -> &mut Self {
+        unsafe {
+            gs_matrix_rotaa4f(x, y, z, angle);
+        }
+        self
+    }
+}
+
+impl Drop for MatrixGuard<'_> {
+    fn drop(&mut self) {
+        unsafe {
+            gs_matrix_pop();
+        }
+    }
+}
+
+/// A viewport rectangle, in pixels.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Viewport {
+    pub x: c_int,
+    pub y: c_int,
+    pub width: c_int,
+    pub height: c_int,
+}
+
+/// Pushes the viewport on creation and pops it when dropped.
+pub struct ViewportGuard<'a> {
+    _context: PhantomData<&'a GraphicsEffectContext>,
+}
+
+impl<'a> ViewportGuard<'a> {
+    pub fn push(_context: &'a GraphicsEffectContext) -> Self {
+        unsafe {
+            gs_viewport_push();
+        }
+        Self {
+            _context: PhantomData,
+        }
+    }
+
+    pub fn set(&mut self, viewport: Viewport) -> &mut Self {
+        unsafe {
+            gs_set_viewport(viewport.x, viewport.y, viewport.width, viewport.height);
+        }
+        self
+    }
+
+    pub fn get(&self) -> Viewport {
+        let mut rect = gs_rect::default();
+        unsafe {
+            gs_get_viewport(&mut rect);
+        }
+        Viewport {
+            x: rect.x,
+            y: rect.y,
+            width: rect.cx,
+            height: rect.cy,
+        }
+    }
+}
+
+impl Drop for ViewportGuard<'_> {
+    fn drop(&mut self) {
+        unsafe {
+            gs_viewport_pop();
+        }
+    }
+}
+
+/// Enables or disables sRGB conversion when writing to the framebuffer,
+/// restoring the previous setting when dropped.
+///
+/// OBS expects filters that sample linear textures to enable this while
+/// drawing, see [`LinearSrgbGuard`].
+pub struct FramebufferSrgbGuard<'a> {
+    previous: bool,
+    _context: PhantomData<&'a GraphicsEffectContext>,
+}
+
+impl<'a> FramebufferSrgbGuard<'a> {
+    pub fn new(_context: &'a GraphicsEffectContext, enable: bool) -> Self {
+        let previous = unsafe {
+            let previous = gs_framebuffer_srgb_enabled();
+            gs_enable_framebuffer_srgb(enable);
+            previous
+        };
+        Self {
+            previous,
+            _context: PhantomData,
+        }
+    }
+}
+
+impl Drop for FramebufferSrgbGuard<'_> {
+    fn drop(&mut self) {
+        unsafe {
+            gs_enable_framebuffer_srgb(self.previous);
+        }
+    }
+}
+
+/// Sets whether textures are sampled as linear sRGB, restoring the previous
+/// setting when dropped.
+pub struct LinearSrgbGuard<'a> {
+    previous: bool,
+    _context: PhantomData<&'a GraphicsEffectContext>,
+}
+
+impl<'a> LinearSrgbGuard<'a> {
+    pub fn new(_context: &'a GraphicsEffectContext, linear_srgb: bool) -> Self {
+        let previous = unsafe { gs_set_linear_srgb(linear_srgb) };
+        Self {
+            previous,
+            _context: PhantomData,
+        }
+    }
+}
+
+impl Drop for LinearSrgbGuard<'_> {
+    fn drop(&mut self) {
+        unsafe {
+            gs_set_linear_srgb(self.previous);
+        }
+    }
+}
@@ -0,0 +1,121 @@
+use obs_sys::{
+    gs_clear, gs_ortho, gs_texrender_begin, gs_texrender_create, gs_texrender_destroy,
+    gs_texrender_end, gs_texrender_get_format, gs_texrender_get_texture, gs_texrender_reset,
+    gs_texrender_t, gs_zstencil_format, gs_zstencil_format_GS_Z16, gs_zstencil_format_GS_Z24_S8,
+    gs_zstencil_format_GS_Z32F, gs_zstencil_format_GS_Z32F_S8X24, gs_zstencil_format_GS_ZS_NONE,
+    GS_CLEAR_COLOR,
+};
+
+use super::{GraphicsColorFormat, GraphicsGuard, GraphicsTextureRef, Vec4};
+use crate::{native_enum, source::VideoRenderContext};
+
+native_enum!(GraphicsZStencilFormat, gs_zstencil_format {
+    None => GS_ZS_NONE,
+    Z16 => GS_Z16,
+    Z24S8 => GS_Z24_S8,
+    Z32F => GS_Z32F,
+    Z32FS8X24 => GS_Z32F_S8X24,
+});
+
+/// Wrapper around [`gs_texrender_t`](https://obsproject.com/docs/reference-libobs-graphics-graphics.html#c.gs_texrender_t),
+/// a render target which can be drawn into and then used as a texture.
+///
+/// This makes multi-pass effects possible: draw the source into a
+/// `GraphicsTexRender`, then draw its [`texture`](Self::texture) with another
+/// effect.
+pub struct GraphicsTexRender {
+    raw: *mut gs_texrender_t,
+}
+
+impl GraphicsTexRender {
+    pub fn new(format: GraphicsColorFormat, zsformat: GraphicsZStencilFormat) -> Self {
+        let raw = GraphicsGuard::with_enter(|| unsafe {
+            gs_texrender_create(format.as_raw(), zsformat.as_raw())
+        });
+        Self { raw }
+    }
+
+    /// Start rendering into the texture, returning a guard which finishes
+    /// rendering when dropped.
+    ///
+    /// The texture is resized if `cx` or `cy` changed since the last frame,
+    /// and the projection is set to an orthographic projection covering
+    /// `(0, 0)` to `(cx, cy)`. Returns `None` if the size is zero, the render
+    /// target could not be created, or it was already rendered since the last
+    /// [`reset`](Self::reset).
+    pub fn begin(
+        &mut self,
+        _render: &mut VideoRenderContext,
+        cx: u32,
+        cy: u32,
+    ) -> Option<TexRenderGuard<'_>> {
+        unsafe {
+            if !gs_texrender_begin(self.raw, cx, cy) {
+                return None;
+            }
+        }
+        let guard = TexRenderGuard { texrender: self };
+        guard.ortho(0., cx as f32, 0., cy as f32, -100., 100.);
+        Some(guard)
+    }
+
+    /// Mark the contents as stale, so the next `begin` redraws it. Call this
+    /// once per frame before rendering, like `gs_texrender_reset`.
+    pub fn reset(&mut self) {
+        unsafe {
+            gs_texrender_reset(self.raw);
+        }
+    }
+
+    /// The texture that was last rendered into, if any.
+    pub fn texture(&self) -> Option<GraphicsTextureRef<'_>> {
+        unsafe { GraphicsTextureRef::from_raw(gs_texrender_get_texture(self.raw)) }
+    }
+
+    pub fn format(&self) -> GraphicsColorFormat {
+        let raw = unsafe { gs_texrender_get_format(self.raw) };
+        GraphicsColorFormat::from_raw(raw).unwrap_or(GraphicsColorFormat::UNKNOWN)
+    }
+
+    pub fn as_ptr(&self) -> *mut gs_texrender_t {
+        self.raw
+    }
+}
+
+impl Drop for GraphicsTexRender {
+    fn drop(&mut self) {
+        GraphicsGuard::with_enter(|| unsafe {
+            gs_texrender_destroy(self.raw);
+        });
+    }
+}
+
+/// Guard returned by [`GraphicsTexRender::begin`]. Everything drawn while it is
+/// alive ends up in the texture.
+pub struct TexRenderGuard<'a> {
+    texrender: &'a mut GraphicsTexRender,
+}
+
+impl TexRenderGuard<'_> {
+    /// Clear the texture to `color`.
+    pub fn clear(&self, color: &Vec4) {
+        unsafe {
+            gs_clear(GS_CLEAR_COLOR, &color.raw, 0., 0);
+        }
+    }
+
+    /// Override the orthographic projection set up by `begin`.
+    pub fn ortho(&self, left: f32, right: f32, top: f32, bottom: f32, znear: f32, zfar: f32) {
+        unsafe {
+            gs_ortho(left, right, top, bottom, znear, zfar);
+        }
+    }
+}
+
+impl Drop for TexRenderGuard<'_> {
+    fn drop(&mut self) {
+        unsafe {
+            gs_texrender_end(self.texrender.raw);
+        }
+    }
+}
@@ -0,0 +1,571 @@
+//! Drawing text without going through the text source plugin.
+//!
+//! Glyphs are rasterized with [fontdue](https://docs.rs/fontdue) into a single
+//! channel atlas texture, and strings are drawn as textured quads with a small
+//! bundled effect. Requires the `text` feature.
+//!
+//! ```ignore
+//! // in `create` or `update`
+//! let font = settings
+//!     .get::<DataObj>(obs_string!("font"))
+//!     .and_then(|font| FontSelection::from_data(&font))
+//!     .and_then(|font| TextRenderer::from_selection(&font).ok());
+//!
+//! // in `video_render`
+//! let context = render.effect_context();
+//! if let Some(text) = &mut self.text {
+//!     text.draw(&context, "Hello!", 10., 10., ColorF32::new(1., 1., 1., 1.));
+//! }
+//! ```
+
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use fontdue::{Font, FontSettings, Metrics};
+
+use super::{
+    display::ColorF32, GraphicsColorFormat, GraphicsEffect, GraphicsEffectContext,
+    GraphicsEffectTextureParam, GraphicsEffectVec4Param, GraphicsTexture, ImmediateRender,
+};
+use crate::{data::DataObj, obs_string, string::ObsString, Error, Result};
+
+const ATLAS_SIZE: u32 = 1024;
+/// Padding between glyphs in the atlas, so linear filtering doesn't bleed.
+const ATLAS_PADDING: u32 = 1;
+/// Glyphs drawn per immediate mode batch, OBS allows at most 512 vertices.
+const GLYPHS_PER_BATCH: usize = 80;
+
+/// `OBS_FONT_BOLD`
+pub const FONT_BOLD: u32 = 1 << 0;
+/// `OBS_FONT_ITALIC`
+pub const FONT_ITALIC: u32 = 1 << 1;
+/// `OBS_FONT_UNDERLINE`
+pub const FONT_UNDERLINE: u32 = 1 << 2;
+/// `OBS_FONT_STRIKEOUT`
+pub const FONT_STRIKEOUT: u32 = 1 << 3;
+
+/// A font picked with a [`FontProp`](crate::properties::FontProp).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FontSelection {
+    pub face: String,
+    pub style: String,
+    pub size: u32,
+    pub flags: u32,
+}
+
+impl FontSelection {
+    /// Read the font object stored by a `FontProp`.
+    pub fn from_data(data: &DataObj) -> Option<Self> {
+        Some(Self {
+            face: data
+                .get::<ObsString>(obs_string!("face"))?
+                .as_str()
+                .to_string(),
+            style: data
+                .get::<ObsString>(obs_string!("style"))
+                .map(|s| s.as_str().to_string())
+                .unwrap_or_default(),
+            size: data.get(obs_string!("size")).unwrap_or(32),
+            flags: data.get(obs_string!("flags")).unwrap_or(0),
+        })
+    }
+
+    pub fn bold(&self) -> bool {
+        self.flags & FONT_BOLD != 0 || self.style.to_lowercase().contains("bold")
+    }
+
+    pub fn italic(&self) -> bool {
+        let style = self.style.to_lowercase();
+        self.flags & FONT_ITALIC != 0 || style.contains("italic") || style.contains("oblique")
+    }
+
+    /// Look for a font file matching the face and style in the system font
+    /// directories.
+    ///
+    /// This is a best-effort match on file names (e.g. `DejaVu Sans` in bold
+    /// matches `DejaVuSans-Bold.ttf`), it does not read font metadata.
+    ///
+    /// Every call walks all font directories, which can take a while. Call it
+    /// from `create` or `update` and keep the result, not from the render
+    /// thread.
+    pub fn find_file(&self) -> Option<PathBuf> {
+        let face = normalize(&self.face);
+        if face.is_empty() {
+            return None;
+        }
+        let (bold, italic) = (self.bold(), self.italic());
+
+        let mut best: Option<(u32, PathBuf)> = None;
+        for dir in font_dirs() {
+            visit_font_files(&dir, &mut |path| {
+                let score = match path.file_stem().and_then(|s| s.to_str()) {
+                    Some(stem) => match file_score(&face, stem, bold, italic) {
+                        Some(score) => score,
+                        None => return,
+                    },
+                    None => return,
+                };
+                if !matches!(&best, Some((best, _)) if *best <= score) {
+                    best = Some((score, path.to_path_buf()));
+                }
+            });
+        }
+        best.map(|(_, path)| path)
+    }
+}
+
+/// How well the file name `stem` matches the normalized `face` and style,
+/// lower is better. `None` if it doesn't match at all.
+fn file_score(face: &str, stem: &str, bold: bool, italic: bool) -> Option<u32> {
+    let stem = normalize(stem);
+    let rest = stem.strip_prefix(face)?;
+    let has_bold = rest.contains("bold");
+    let has_italic = rest.contains("italic") || rest.contains("oblique");
+    if has_bold != bold || has_italic != italic {
+        return None;
+    }
+    // prefer the plainest matching file, e.g. `regular` over
+    // `condensedbold` variants
+    let extra = rest
+        .replace("bold", "")
+        .replace("italic", "")
+        .replace("oblique", "")
+        .replace("regular", "");
+    Some(extra.len() as u32)
+}
+
+fn normalize(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+fn font_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    if cfg!(target_os = "windows") {
+        let windir = std::env::var_os("WINDIR").unwrap_or_else(|| "C:\\Windows".into());
+        dirs.push(Path::new(&windir).join("Fonts"));
+        if let Some(local) = std::env::var_os("LOCALAPPDATA") {
+            dirs.push(Path::new(&local).join("Microsoft\\Windows\\Fonts"));
+        }
+    } else if cfg!(target_os = "macos") {
+        dirs.push("/System/Library/Fonts".into());
+        dirs.push("/Library/Fonts".into());
+        if let Some(home) = std::env::var_os("HOME") {
+            dirs.push(Path::new(&home).join("Library/Fonts"));
+        }
+    } else {
+        if let Some(data) = std::env::var_os("XDG_DATA_HOME") {
+            dirs.push(Path::new(&data).join("fonts"));
+        }
+        if let Some(home) = std::env::var_os("HOME") {
+            dirs.push(Path::new(&home).join(".local/share/fonts"));
+            dirs.push(Path::new(&home).join(".fonts"));
+        }
+        dirs.push("/usr/local/share/fonts".into());
+        dirs.push("/usr/share/fonts".into());
+    }
+    dirs
+}
+
+fn visit_font_files(dir: &Path, func: &mut dyn FnMut(&Path)) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    for entry in entries.flatten() {
+        // `DirEntry::file_type` doesn't follow symlinks, so a link back up the
+        // tree can't send this into a loop. Links to font files are still
+        // picked up by their extension below.
+        let is_dir = match entry.file_type() {
+            Ok(file_type) => file_type.is_dir(),
+            Err(_) => continue,
+        };
+        let path = entry.path();
+        if is_dir {
+            visit_font_files(&path, func);
+        } else {
+            let is_font = path
+                .extension()
+                .and_then(|e| e.to_str())
+                .map(|e| matches!(e.to_lowercase().as_str(), "ttf" | "otf" | "ttc"))
+                .unwrap_or(false);
+            if is_font {
+                func(&path);
+            }
+        }
+    }
+}
+
+/// Simple shelf packing, rectangles are placed left to right in rows.
+struct ShelfPacker {
+    size: u32,
+    cursor: (u32, u32),
+    row_height: u32,
+}
+
+impl ShelfPacker {
+    fn new(size: u32) -> Self {
+        Self {
+            size,
+            cursor: (ATLAS_PADDING, ATLAS_PADDING),
+            row_height: 0,
+        }
+    }
+
+    /// Find room for a `width` by `height` rectangle, returning its top left
+    /// corner. `None` once the atlas is full, or if the rectangle is larger
+    /// than the atlas.
+    fn place(&mut self, width: u32, height: u32) -> Option<(u32, u32)> {
+        if width + ATLAS_PADDING * 2 > self.size || height + ATLAS_PADDING * 2 > self.size {
+            return None;
+        }
+        if self.cursor.0 + width + ATLAS_PADDING > self.size {
+            self.cursor = (
+                ATLAS_PADDING,
+                self.cursor.1 + self.row_height + ATLAS_PADDING,
+            );
+            self.row_height = 0;
+        }
+        if self.cursor.1 + height + ATLAS_PADDING > self.size {
+            return None;
+        }
+        let position = self.cursor;
+        self.cursor.0 += width + ATLAS_PADDING;
+        self.row_height = self.row_height.max(height);
+        Some(position)
+    }
+
+    fn clear(&mut self) {
+        *self = Self::new(self.size);
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Glyph {
+    metrics: Metrics,
+    /// Position in the atlas, in pixels.
+    x: u32,
+    y: u32,
+}
+
+/// Draws strings with a single font at a fixed size.
+pub struct TextRenderer {
+    font: Font,
+    px: f32,
+    effect: GraphicsEffect,
+    atlas: Vec<u8>,
+    texture: GraphicsTexture,
+    dirty: bool,
+    glyphs: HashMap<char, Glyph>,
+    packer: ShelfPacker,
+    generation: u32,
+}
+
+impl TextRenderer {
+    /// Create a renderer from the contents of a TTF or OTF file, rasterizing
+    /// glyphs at `px` pixels per em.
+    pub fn new(font_data: &[u8], px: f32) -> Result<Self> {
+        let font = Font::from_bytes(
+            font_data,
+            FontSettings {
+                scale: px,
+                ..FontSettings::default()
+            },
+        )
+        .map_err(|e| Error::Font(e.to_string()))?;
+        let effect = GraphicsEffect::from_effect_string(
+            obs_string!(include_str!("./text.effect")),
+            obs_string!("text.effect"),
+        )?;
+
+        Ok(Self {
+            font,
+            px,
+            effect,
+            atlas: vec![0; (ATLAS_SIZE * ATLAS_SIZE) as usize],
+            texture: GraphicsTexture::new(ATLAS_SIZE, ATLAS_SIZE, GraphicsColorFormat::R8),
+            dirty: true,
+            glyphs: HashMap::new(),
+            packer: ShelfPacker::new(ATLAS_SIZE),
+            generation: 0,
+        })
+    }
+
+    /// Load the font file at `path`.
+    pub fn from_file(path: &Path, px: f32) -> Result<Self> {
+        let data = fs::read(path).map_err(|e| Error::Font(format!("{}: {}", path.display(), e)))?;
+        Self::new(&data, px)
+    }
+
+    /// Find and load the font picked in a [`FontProp`](crate::properties::FontProp),
+    /// see [`FontSelection::find_file`].
+    pub fn from_selection(selection: &FontSelection) -> Result<Self> {
+        let path = selection
+            .find_file()
+            .ok_or_else(|| Error::Font(format!("no font file found for {}", selection.face)))?;
+        Self::from_file(&path, selection.size as f32)
+    }
+
+    pub fn size(&self) -> f32 {
+        self.px
+    }
+
+    /// Distance between the baselines of two lines.
+    pub fn line_height(&self) -> f32 {
+        self.font
+            .horizontal_line_metrics(self.px)
+            .map(|m| m.new_line_size)
+            .unwrap_or(self.px)
+    }
+
+    fn ascent(&self) -> f32 {
+        self.font
+            .horizontal_line_metrics(self.px)
+            .map(|m| m.ascent)
+            .unwrap_or(self.px)
+    }
+
+    fn glyph(&mut self, c: char) -> Option<Glyph> {
+        if let Some(glyph) = self.glyphs.get(&c) {
+            return Some(*glyph);
+        }
+
+        let (metrics, bitmap) = self.font.rasterize(c, self.px);
+        let (width, height) = (metrics.width as u32, metrics.height as u32);
+        if width + ATLAS_PADDING * 2 > ATLAS_SIZE || height + ATLAS_PADDING * 2 > ATLAS_SIZE {
+            return None;
+        }
+
+        let (x, y) = match self.packer.place(width, height) {
+            Some(position) => position,
+            None => {
+                // atlas is full, start over with only the glyphs drawn from now on
+                self.clear_atlas();
+                self.packer.place(width, height)?
+            }
+        };
+        for row in 0..height as usize {
+            let src = &bitmap[row * width as usize..(row + 1) * width as usize];
+            let start = (y as usize + row) * ATLAS_SIZE as usize + x as usize;
+            self.atlas[start..start + width as usize].copy_from_slice(src);
+        }
+        self.dirty = true;
+
+        let glyph = Glyph { metrics, x, y };
+        self.glyphs.insert(c, glyph);
+        Some(glyph)
+    }
+
+    fn clear_atlas(&mut self) {
+        self.atlas.iter_mut().for_each(|p| *p = 0);
+        self.glyphs.clear();
+        self.packer.clear();
+        self.dirty = true;
+        self.generation = self.generation.wrapping_add(1);
+    }
+
+    /// Lay out `text` with its top left corner at `(x, y)`, calling `func`
+    /// with each visible glyph and its top left position.
+    fn layout<F: FnMut(Glyph, f32, f32)>(&mut self, text: &str, x: f32, y: f32, mut func: F) {
+        let line_height = self.line_height();
+        let mut baseline = y + self.ascent();
+        let mut pen = x;
+        let mut previous = None;
+
+        for c in text.chars() {
+            if c == '\n' {
+                pen = x;
+                baseline += line_height;
+                previous = None;
+                continue;
+            }
+            if let Some(kern) = previous.and_then(|p| self.font.horizontal_kern(p, c, self.px)) {
+                pen += kern;
+            }
+            if let Some(glyph) = self.glyph(c) {
+                let m = glyph.metrics;
+                if m.width > 0 && m.height > 0 {
+                    let left = pen + m.xmin as f32;
+                    let top = baseline - (m.ymin as f32 + m.height as f32);
+                    func(glyph, left, top);
+                }
+                pen += m.advance_width;
+            }
+            previous = Some(c);
+        }
+    }
+
+    /// The width and height `text` takes up when drawn.
+    pub fn measure(&mut self, text: &str) -> (f32, f32) {
+        let line_height = self.line_height();
+        let mut width: f32 = 0.;
+        let mut lines = 0;
+        for line in text.split('\n') {
+            lines += 1;
+            let mut pen = 0.;
+            let mut previous = None;
+            for c in line.chars() {
+                if let Some(kern) = previous.and_then(|p| self.font.horizontal_kern(p, c, self.px))
+                {
+                    pen += kern;
+                }
+                pen += self.font.metrics(c, self.px).advance_width;
+                previous = Some(c);
+            }
+            width = width.max(pen);
+        }
+        (width, lines as f32 * line_height)
+    }
+
+    /// Draw `text` with its top left corner at `(x, y)`. Lines are separated
+    /// by `\n`.
+    pub fn draw(
+        &mut self,
+        context: &GraphicsEffectContext,
+        text: &str,
+        x: f32,
+        y: f32,
+        color: ColorF32,
+    ) {
+        let mut quads = Vec::new();
+        let generation = self.generation;
+        self.layout(text, x, y, |glyph, left, top| {
+            quads.push((glyph, left, top))
+        });
+        if self.generation != generation {
+            // the atlas filled up and was cleared part way through, lay out
+            // again so every glyph points into the new atlas
+            quads.clear();
+            self.layout(text, x, y, |glyph, left, top| {
+                quads.push((glyph, left, top))
+            });
+        }
+        if quads.is_empty() {
+            return;
+        }
+
+        if self.dirty {
+            self.texture.set_image(&self.atlas, ATLAS_SIZE, false);
+            self.dirty = false;
+        }
+
+        if let Some(mut image) = self
+            .effect
+            .get_effect_param_by_name::<GraphicsEffectTextureParam>(obs_string!("image"))
+        {
+            image.set_texture(context, &self.texture);
+        }
+        if let Some(mut param) = self
+            .effect
+            .get_effect_param_by_name::<GraphicsEffectVec4Param>(obs_string!("color"))
+        {
+            param.set_color(context, color);
+        }
+
+        let mut technique = match self.effect.get_technique(obs_string!("Draw")) {
+            Some(technique) => technique,
+            None => return,
+        };
+        technique.for_each_pass(context, |_| {
+            for batch in quads.chunks(GLYPHS_PER_BATCH) {
+                let mut render = ImmediateRender::start(context);
+                for (glyph, left, top) in batch {
+                    draw_glyph(&mut render, glyph, *left, *top);
+                }
+                render.draw(super::GraphicsDrawMode::Tris);
+            }
+        });
+    }
+}
+
+fn draw_glyph(render: &mut ImmediateRender, glyph: &Glyph, left: f32, top: f32) {
+    let (w, h) = (glyph.metrics.width as f32, glyph.metrics.height as f32);
+    let size = ATLAS_SIZE as f32;
+    let (u0, v0) = (glyph.x as f32 / size, glyph.y as f32 / size);
+    let (u1, v1) = (u0 + w / size, v0 + h / size);
+    let (right, bottom) = (left + w, top + h);
+
+    for &(x, y, u, v) in &[
+        (left, top, u0, v0),
+        (right, top, u1, v0),
+        (left, bottom, u0, v1),
+        (right, top, u1, v0),
+        (right, bottom, u1, v1),
+        (left, bottom, u0, v1),
+    ] {
+        render.texcoord(u, v, 0).vertex2f(x, y);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_names() {
+        assert_eq!(normalize("DejaVu Sans"), "dejavusans");
+        assert_eq!(normalize("DejaVuSans-Bold"), "dejavusansbold");
+        assert_eq!(normalize("Noto_Sans CJK.JP"), "notosanscjkjp");
+        assert_eq!(normalize(" - "), "");
+    }
+
+    #[test]
+    fn file_scores() {
+        let face = normalize("DejaVu Sans");
+        let bold = file_score(&face, "DejaVuSans-Bold", true, false).unwrap();
+        let condensed = file_score(&face, "DejaVuSansCondensed-Bold", true, false).unwrap();
+        assert!(bold < condensed);
+
+        assert_eq!(file_score(&face, "DejaVuSans", false, false), Some(0));
+        assert_eq!(
+            file_score(&face, "DejaVuSans-Regular", false, false),
+            Some(0)
+        );
+        assert_eq!(file_score(&face, "DejaVuSans-Bold", false, false), None);
+        assert_eq!(file_score(&face, "DejaVuSans", true, false), None);
+        assert_eq!(
+            file_score(&face, "DejaVuSans-BoldOblique", true, true),
+            Some(0)
+        );
+        assert_eq!(file_score(&face, "DejaVuSerif", false, false), None);
+    }
+
+    #[test]
+    fn shelf_packing() {
+        let mut packer = ShelfPacker::new(32);
+        let sizes = [(10, 4), (10, 8), (10, 2), (5, 5), (20, 3), (30, 1)];
+        let mut placed: Vec<(u32, u32, u32, u32)> = Vec::new();
+        for &(w, h) in &sizes {
+            let (x, y) = packer.place(w, h).unwrap();
+            assert!(x >= ATLAS_PADDING && x + w + ATLAS_PADDING <= 32);
+            assert!(y >= ATLAS_PADDING && y + h + ATLAS_PADDING <= 32);
+            for &(ox, oy, ow, oh) in &placed {
+                let apart = x >= ox + ow + ATLAS_PADDING
+                    || ox >= x + w + ATLAS_PADDING
+                    || y >= oy + oh + ATLAS_PADDING
+                    || oy >= y + h + ATLAS_PADDING;
+                assert!(apart, "{:?} overlaps {:?}", (x, y, w, h), (ox, oy, ow, oh));
+            }
+            placed.push((x, y, w, h));
+        }
+
+        // two fit in the first row, the third starts a shelf below the
+        // taller of them
+        assert_eq!(placed[0], (1, 1, 10, 4));
+        assert_eq!(placed[1], (12, 1, 10, 8));
+        assert_eq!(placed[2], (1, 10, 10, 2));
+        assert_eq!(placed[3], (12, 10, 5, 5));
+        assert_eq!(placed[4], (1, 16, 20, 3));
+        assert_eq!(placed[5], (1, 20, 30, 1));
+
+        assert_eq!(packer.place(10, 20), None);
+        packer.clear();
+        assert_eq!(packer.place(10, 20), Some((1, 1)));
+        assert_eq!(packer.place(31, 1), None);
+    }
+}
@@ -0,0 +1,352 @@
+use obs_sys::{
+    gs_color, gs_draw, gs_draw_mode, gs_draw_mode_GS_LINES, gs_draw_mode_GS_LINESTRIP,
+    gs_draw_mode_GS_POINTS, gs_draw_mode_GS_TRIS, gs_draw_mode_GS_TRISTRIP, gs_draw_sprite,
+    gs_draw_sprite_subregion, gs_effect_get_param_by_name, gs_effect_set_color,
+    gs_load_indexbuffer, gs_load_vertexbuffer, gs_normal3f, gs_render_save, gs_render_start,
+    gs_render_stop, gs_texcoord, gs_tvertarray, gs_vb_data, gs_vertbuffer_t, gs_vertex2f,
+    gs_vertex3f, gs_vertexbuffer_create, gs_vertexbuffer_destroy, gs_vertexbuffer_flush_direct,
+    GS_DUP_BUFFER, GS_DYNAMIC, GS_FLIP_V,
+};
+use std::{marker::PhantomData, os::raw::c_int, ptr::null_mut};
+
+use super::{
+    display::Color, GraphicsBaseEffect, GraphicsEffect, GraphicsEffectContext, GraphicsGuard,
+    GraphicsTexture, Vec2, Vec3,
+};
+use crate::{native_enum, obs_string};
+
+native_enum!(GraphicsDrawMode, gs_draw_mode {
+    Points => GS_POINTS,
+    Lines => GS_LINES,
+    LineStrip => GS_LINESTRIP,
+    Tris => GS_TRIS,
+    TriStrip => GS_TRISTRIP,
+});
+
+/// Immediate mode geometry, built with
+/// [`gs_render_start`](https://obsproject.com/docs/reference-libobs-graphics-graphics.html#c.gs_render_start).
+///
+/// Vertices are added one at a time, with [`color`](Self::color),
+/// [`texcoord`](Self::texcoord) and [`normal`](Self::normal) applying to the
+/// next vertex. The geometry is then either drawn straight away with
+/// [`draw`](Self::draw), or saved into a [`GraphicsVertexBuffer`] with
+/// [`save`](Self::save).
+pub struct ImmediateRender<'a> {
+    finished: bool,
+    _context: PhantomData<&'a GraphicsEffectContext>,
+}
+
+impl<'a> ImmediateRender<'a> {
+    pub fn start(_context: &'a GraphicsEffectContext) -> Self {
+        unsafe {
+            gs_render_start(true);
+        }
+        Self {
+            finished: false,
+            _context: PhantomData,
+        }
+    }
+
+    pub fn vertex2f(&mut self, x: f32, y: f32) -> &mut Self {
+        unsafe {
+            gs_vertex2f(x, y);
+        }
+        self
+    }
+
+    pub fn vertex3f(&mut self, x: f32, y: f32, z: f32) -> &mut Self {
+        unsafe {
+            gs_vertex3f(x, y, z);
+        }
+        self
+    }
+
+    pub fn normal(&mut self, x: f32, y: f32, z: f32) -> &mut Self {
+        unsafe {
+            gs_normal3f(x, y, z);
+        }
+        self
+    }
+
+    pub fn color(&mut self, color: Color) -> &mut Self {
+        unsafe {
+            gs_color(color.as_rgba());
+        }
+        self
+    }
+
+    pub fn texcoord(&mut self, x: f32, y: f32, unit: c_int) -> &mut Self {
+        unsafe {
+            gs_texcoord(x, y, unit);
+        }
+        self
+    }
+
+    /// Draw the geometry with the current effect. Must be called inside a
+    /// technique pass.
+    pub fn draw(mut self, mode: GraphicsDrawMode) {
+        self.finished = true;
+        unsafe {
+            gs_render_stop(mode.as_raw());
+        }
+    }
+
+    /// Save the geometry into a vertex buffer, so it can be drawn again later.
+    pub fn save(mut self) -> Option<GraphicsVertexBuffer> {
+        self.finished = true;
+        let raw = unsafe { gs_render_save() };
+        if raw.is_null() {
+            None
+        } else {
+            Some(GraphicsVertexBuffer { raw })
+        }
+    }
+}
+
+impl Drop for ImmediateRender<'_> {
+    fn drop(&mut self) {
+        if !self.finished {
+            unsafe {
+                let raw = gs_render_save();
+                if !raw.is_null() {
+                    gs_vertexbuffer_destroy(raw);
+                }
+            }
+        }
+    }
+}
+
+/// Rust owned vertex data for [`GraphicsVertexBuffer`], the equivalent of
+/// `gs_vb_data`.
+///
+/// Every non-empty attribute must have one entry per point. Each entry of
+/// `tex_coords` is a separate texture unit.
+#[derive(Clone, Default)]
+pub struct VertexBufferData {
+    pub points: Vec<Vec3>,
+    pub normals: Vec<Vec3>,
+    pub tangents: Vec<Vec3>,
+    pub colors: Vec<u32>,
+    pub tex_coords: Vec<Vec<Vec2>>,
+}
+
+impl VertexBufferData {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    fn is_valid(&self) -> bool {
+        let num = self.points.len();
+        let matches = |len: usize| len == 0 || len == num;
+        num > 0
+            && matches(self.normals.len())
+            && matches(self.tangents.len())
+            && matches(self.colors.len())
+            && self.tex_coords.iter().all(|unit| unit.len() == num)
+    }
+
+    /// Borrow the data as a `gs_vb_data`. OBS must not write through the
+    /// returned pointers.
+    fn as_raw(&self, tvarray: &mut Vec<gs_tvertarray>) -> gs_vb_data {
+        fn ptr_or_null<T, R>(data: &[T]) -> *mut R {
+            if data.is_empty() {
+                null_mut()
+            } else {
+                data.as_ptr() as *mut R
+            }
+        }
+
+        tvarray.clear();
+        tvarray.extend(self.tex_coords.iter().map(|unit| gs_tvertarray {
+            width: 2,
+            array: unit.as_ptr() as *mut _,
+        }));
+
+        gs_vb_data {
+            num: self.points.len(),
+            points: ptr_or_null(&self.points),
+            normals: ptr_or_null(&self.normals),
+            tangents: ptr_or_null(&self.tangents),
+            colors: ptr_or_null(&self.colors),
+            num_tex: tvarray.len(),
+            tvarray: ptr_or_null(tvarray),
+        }
+    }
+}
+
+/// Wrapper around [`gs_vertbuffer_t`](https://obsproject.com/docs/reference-libobs-graphics-graphics.html#c.gs_vertbuffer_t)
+pub struct GraphicsVertexBuffer {
+    raw: *mut gs_vertbuffer_t,
+}
+
+impl GraphicsVertexBuffer {
+    /// Upload `data` to the GPU. OBS keeps its own copy, so `data` can be
+    /// dropped or reused afterwards. Only `dynamic` buffers can be
+    /// [`update`](Self::update)d.
+    ///
+    /// Returns `None` if the attributes have mismatched lengths or the buffer
+    /// could not be created.
+    pub fn new(data: &VertexBufferData, dynamic: bool) -> Option<Self> {
+        if !data.is_valid() {
+            return None;
+        }
+        let flags = if dynamic {
+            GS_DUP_BUFFER | GS_DYNAMIC
+        } else {
+            GS_DUP_BUFFER
+        };
+        let mut tvarray = Vec::new();
+        let mut raw_data = data.as_raw(&mut tvarray);
+        let raw =
+            GraphicsGuard::with_enter(|| unsafe { gs_vertexbuffer_create(&mut raw_data, flags) });
+        if raw.is_null() {
+            None
+        } else {
+            Some(Self { raw })
+        }
+    }
+
+    /// Replace the contents of a dynamic buffer. `data` must have the same
+    /// number of points and attributes the buffer was created with.
+    pub fn update(&mut self, data: &VertexBufferData) {
+        if !data.is_valid() {
+            return;
+        }
+        let mut tvarray = Vec::new();
+        let raw_data = data.as_raw(&mut tvarray);
+        GraphicsGuard::with_enter(|| unsafe {
+            gs_vertexbuffer_flush_direct(self.raw, &raw_data);
+        });
+    }
+
+    /// Draw `num_verts` vertices starting at `start_vert` with the current
+    /// effect, or the whole buffer if `num_verts` is 0. Must be called inside
+    /// a technique pass.
+    pub fn draw(
+        &self,
+        _context: &GraphicsEffectContext,
+        mode: GraphicsDrawMode,
+        start_vert: u32,
+        num_verts: u32,
+    ) {
+        unsafe {
+            gs_load_vertexbuffer(self.raw);
+            gs_load_indexbuffer(null_mut());
+            gs_draw(mode.as_raw(), start_vert, num_verts);
+            gs_load_vertexbuffer(null_mut());
+        }
+    }
+
+    pub fn as_ptr(&self) -> *mut gs_vertbuffer_t {
+        self.raw
+    }
+}
+
+impl Drop for GraphicsVertexBuffer {
+    fn drop(&mut self) {
+        GraphicsGuard::with_enter(|| unsafe {
+            gs_vertexbuffer_destroy(self.raw);
+        });
+    }
+}
+
+/// Draw a filled rectangle with the current effect. Must be called inside a
+/// technique pass.
+pub fn draw_rect(context: &GraphicsEffectContext, x: f32, y: f32, cx: f32, cy: f32) {
+    let mut render = ImmediateRender::start(context);
+    render
+        .vertex2f(x, y)
+        .vertex2f(x + cx, y)
+        .vertex2f(x, y + cy)
+        .vertex2f(x + cx, y + cy);
+    render.draw(GraphicsDrawMode::TriStrip);
+}
+
+/// Draw a one pixel wide line with the current effect. Must be called inside a
+/// technique pass.
+pub fn draw_line(context: &GraphicsEffectContext, x1: f32, y1: f32, x2: f32, y2: f32) {
+    let mut render = ImmediateRender::start(context);
+    render.vertex2f(x1, y1).vertex2f(x2, y2);
+    render.draw(GraphicsDrawMode::Lines);
+}
+
+/// Draw `texture` as a sprite at the origin with the current effect, scaled to
+/// `cx` by `cy` (or the texture size if 0). The texture still needs to be set
+/// on the effect's `image` parameter. Must be called inside a technique pass.
+pub fn draw_sprite(
+    _context: &GraphicsEffectContext,
+    texture: &GraphicsTexture,
+    flip: bool,
+    cx: u32,
+    cy: u32,
+) {
+    let flip = if flip { GS_FLIP_V } else { 0 };
+    unsafe {
+        gs_draw_sprite(texture.as_ptr(), flip, cx, cy);
+    }
+}
+
+/// Draw the region `(x, y, cx, cy)` of `texture` as a sprite at the origin with
+/// the current effect. Must be called inside a technique pass.
+pub fn draw_sprite_subregion(
+    _context: &GraphicsEffectContext,
+    texture: &GraphicsTexture,
+    flip: bool,
+    (x, y): (u32, u32),
+    (cx, cy): (u32, u32),
+) {
+    let flip = if flip { GS_FLIP_V } else { 0 };
+    unsafe {
+        gs_draw_sprite_subregion(texture.as_ptr(), flip, x, y, cx, cy);
+    }
+}
+
+/// Run `func` inside the passes of the built in solid color effect, with its
+/// color set to `color`.
+fn with_solid_effect<F: FnMut(&GraphicsEffectContext)>(
+    context: &GraphicsEffectContext,
+    color: Color,
+    mut func: F,
+) {
+    let mut effect = match GraphicsEffect::base(GraphicsBaseEffect::Solid) {
+        Some(effect) => effect,
+        None => return,
+    };
+    let name = obs_string!("color");
+    let argb = u32::from_be_bytes([color.a, color.r, color.g, color.b]);
+    unsafe {
+        let param = gs_effect_get_param_by_name(effect.as_ptr(), name.as_ptr());
+        gs_effect_set_color(param, argb);
+    }
+    if let Some(mut technique) = effect.get_technique(obs_string!("Solid")) {
+        technique.for_each_pass(context, |_| func(context));
+    }
+}
+
+/// Draw a rectangle filled with `color`.
+pub fn draw_solid_rect(
+    context: &GraphicsEffectContext,
+    (x, y): (f32, f32),
+    (cx, cy): (f32, f32),
+    color: Color,
+) {
+    with_solid_effect(context, color, |context| draw_rect(context, x, y, cx, cy));
+}
+
+/// Draw a one pixel wide line of `color`.
+pub fn draw_solid_line(
+    context: &GraphicsEffectContext,
+    (x1, y1): (f32, f32),
+    (x2, y2): (f32, f32),
+    color: Color,
+) {
+    with_solid_effect(context, color, |context| draw_line(context, x1, y1, x2, y2));
+}
@@ -1,55 +1,255 @@
-use obs_sys::{audio_output_get_channels, audio_output_get_sample_rate, audio_t, obs_audio_data};
+use obs_sys::{
+    audio_output_get_channels, audio_output_get_info, audio_output_get_sample_rate, audio_t,
+    obs_audio_data, obs_get_audio, speaker_layout, speaker_layout_SPEAKERS_2POINT1,
+    speaker_layout_SPEAKERS_4POINT0, speaker_layout_SPEAKERS_4POINT1,
+    speaker_layout_SPEAKERS_5POINT1, speaker_layout_SPEAKERS_7POINT1, speaker_layout_SPEAKERS_MONO,
+    speaker_layout_SPEAKERS_STEREO, speaker_layout_SPEAKERS_UNKNOWN, MAX_AV_PLANES,
+};
 
-pub struct AudioDataContext {
+use crate::native_enum;
+
+native_enum!(SpeakerLayout, speaker_layout {
+    /// Unknown setting, libobs falls back to stereo
+    Unknown => SPEAKERS_UNKNOWN,
+    Mono => SPEAKERS_MONO,
+    Stereo => SPEAKERS_STEREO,
+    TwoPointOne => SPEAKERS_2POINT1,
+    FourPointZero => SPEAKERS_4POINT0,
+    FourPointOne => SPEAKERS_4POINT1,
+    FivePointOne => SPEAKERS_5POINT1,
+    SevenPointOne => SPEAKERS_7POINT1,
+});
+
+/// Position of the speaker a channel is played on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Speaker {
+    FrontLeft,
+    FrontRight,
+    FrontCenter,
+    LowFrequency,
+    RearLeft,
+    RearRight,
+    RearCenter,
+    SideLeft,
+    SideRight,
+}
+
+impl SpeakerLayout {
+    /// The speaker of each channel, in channel order.
+    pub fn speakers(&self) -> &'static [Speaker] {
+        use Speaker::*;
+        match self {
+            SpeakerLayout::Unknown => &[],
+            SpeakerLayout::Mono => &[FrontCenter],
+            SpeakerLayout::Stereo => &[FrontLeft, FrontRight],
+            SpeakerLayout::TwoPointOne => &[FrontLeft, FrontRight, LowFrequency],
+            SpeakerLayout::FourPointZero => &[FrontLeft, FrontRight, FrontCenter, RearCenter],
+            SpeakerLayout::FourPointOne => {
+                &[FrontLeft, FrontRight, FrontCenter, LowFrequency, RearCenter]
+            }
+            SpeakerLayout::FivePointOne => &[
+                FrontLeft,
+                FrontRight,
+                FrontCenter,
+                LowFrequency,
+                RearLeft,
+                RearRight,
+            ],
+            SpeakerLayout::SevenPointOne => &[
+                FrontLeft,
+                FrontRight,
+                FrontCenter,
+                LowFrequency,
+                RearLeft,
+                RearRight,
+                SideLeft,
+                SideRight,
+            ],
+        }
+    }
+
+    pub fn channels(&self) -> usize {
+        self.speakers().len()
+    }
+
+    /// The index of the channel played on `speaker`, if the layout has one.
+    pub fn channel_of(&self, speaker: Speaker) -> Option<usize> {
+        self.speakers().iter().position(|s| *s == speaker)
+    }
+}
+
+/// A block of planar float audio, as passed to
+/// [`FilterAudioSource`](crate::source::traits::FilterAudioSource).
+pub struct AudioFrame {
     pointer: *mut obs_audio_data,
+    channels: usize,
 }
 
-impl AudioDataContext {
+/// Previous name of [`AudioFrame`].
+pub type AudioDataContext = AudioFrame;
+
+impl AudioFrame {
+    /// Wrap audio using the channel count of the global audio output.
     pub fn from_raw(pointer: *mut obs_audio_data) -> Self {
-        Self { pointer }
+        let channels = unsafe {
+            let audio = obs_get_audio();
+            if audio.is_null() {
+                MAX_AV_PLANES as usize
+            } else {
+                audio_output_get_channels(audio)
+            }
+        };
+        Self::from_raw_with_channels(pointer, channels)
     }
 
-    pub fn frames(&self) -> usize {
-        unsafe {
-            self.pointer
-                .as_ref()
-                .expect("Audio pointer was null!")
-                .frames as usize
+    /// Wrap audio with `channels` planes. Planes which are null are not
+    /// counted.
+    pub fn from_raw_with_channels(pointer: *mut obs_audio_data, channels: usize) -> Self {
+        Self {
+            pointer,
+            channels: channels.min(MAX_AV_PLANES as usize),
         }
     }
 
+    fn raw(&self) -> &obs_audio_data {
+        unsafe { self.pointer.as_ref().expect("Audio pointer was null!") }
+    }
+
+    /// Samples in each channel.
+    pub fn frames(&self) -> usize {
+        self.raw().frames as usize
+    }
+
     pub fn channels(&self) -> usize {
+        self.raw()
+            .data
+            .iter()
+            .take(self.channels)
+            .take_while(|plane| !plane.is_null())
+            .count()
+    }
+
+    /// Timestamp of the first sample in nanoseconds.
+    pub fn timestamp(&self) -> u64 {
+        self.raw().timestamp
+    }
+
+    pub fn set_timestamp(&mut self, timestamp: u64) {
+        unsafe { (*self.pointer).timestamp = timestamp }
+    }
+
+    pub fn channel(&self, channel: usize) -> Option<&[f32]> {
+        if channel >= self.channels() {
+            return None;
+        }
+        let raw = self.raw();
         unsafe {
-            self.pointer
-                .as_ref()
-                .expect("Audio pointer was null!")
-                .data
-                .len()
+            Some(std::slice::from_raw_parts(
+                raw.data[channel] as *const f32,
+                raw.frames as usize,
+            ))
         }
     }
 
-    pub fn get_channel_as_mut_slice(&self, channel: usize) -> Option<&'_ mut [f32]> {
+    pub fn channel_mut(&mut self, channel: usize) -> Option<&mut [f32]> {
+        if channel >= self.channels() {
+            return None;
+        }
+        let raw = self.raw();
         unsafe {
-            let data = self.pointer.as_ref()?.data;
+            Some(std::slice::from_raw_parts_mut(
+                raw.data[channel] as *mut f32,
+                raw.frames as usize,
+            ))
+        }
+    }
 
-            if channel >= data.len() {
-                return None;
-            }
+    pub fn get_channel_as_mut_slice(&mut self, channel: usize) -> Option<&mut [f32]> {
+        self.channel_mut(channel)
+    }
 
-            let frames = self.pointer.as_ref()?.frames;
+    pub fn iter_channels(&self) -> impl Iterator<Item = &[f32]> {
+        (0..self.channels()).filter_map(move |channel| self.channel(channel))
+    }
 
-            Some(core::slice::from_raw_parts_mut(
-                data[channel] as *mut f32,
-                frames as usize,
-            ))
+    /// All channels at once, e.g. to mix between them.
+    pub fn iter_channels_mut(&mut self) -> impl Iterator<Item = &mut [f32]> {
+        let raw = self.raw();
+        let frames = raw.frames as usize;
+        raw.data[..self.channels()]
+            .iter()
+            // each channel is a separate buffer, so the slices don't alias
+            .map(move |plane| unsafe { std::slice::from_raw_parts_mut(*plane as *mut f32, frames) })
+    }
+
+    /// Copy the channels into `out` as interleaved samples, returning the
+    /// number of frames copied.
+    pub fn interleave_into(&self, out: &mut [f32]) -> usize {
+        let channels: Vec<&[f32]> = self.iter_channels().collect();
+        interleave(&channels, out)
+    }
+
+    pub fn to_interleaved(&self) -> Vec<f32> {
+        let mut out = vec![0.; self.frames() * self.channels()];
+        self.interleave_into(&mut out);
+        out
+    }
+
+    /// Overwrite the channels with interleaved samples from `input`,
+    /// returning the number of frames copied.
+    pub fn deinterleave_from(&mut self, input: &[f32]) -> usize {
+        let mut channels: Vec<&mut [f32]> = self.iter_channels_mut().collect();
+        deinterleave(input, &mut channels)
+    }
+}
+
+/// Interleave planar `channels` into `out`, stopping when either runs out.
+/// Returns the number of frames written.
+pub fn interleave(channels: &[&[f32]], out: &mut [f32]) -> usize {
+    let count = channels.len();
+    if count == 0 {
+        return 0;
+    }
+    let frames = channels
+        .iter()
+        .map(|c| c.len())
+        .min()
+        .unwrap_or(0)
+        .min(out.len() / count);
+    for (idx, channel) in channels.iter().enumerate() {
+        for (frame, sample) in channel[..frames].iter().enumerate() {
+            out[frame * count + idx] = *sample;
+        }
+    }
+    frames
+}
+
+/// Split interleaved `input` into planar `channels`, stopping when either
+/// runs out. Returns the number of frames written.
+pub fn deinterleave(input: &[f32], channels: &mut [&mut [f32]]) -> usize {
+    let count = channels.len();
+    if count == 0 {
+        return 0;
+    }
+    let frames = channels
+        .iter()
+        .map(|c| c.len())
+        .min()
+        .unwrap_or(0)
+        .min(input.len() / count);
+    for (idx, channel) in channels.iter_mut().enumerate() {
+        for (frame, sample) in channel[..frames].iter_mut().enumerate() {
+            *sample = input[frame * count + idx];
         }
     }
+    frames
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct AudioInfo {
     pub sample_rate: usize,
     pub channels: usize,
+    pub speakers: SpeakerLayout,
 }
 
 pub struct AudioRef {
@@ -65,6 +265,7 @@ impl AudioRef {
         AudioInfo {
             sample_rate: self.sample_rate(),
             channels: self.channels(),
+            speakers: self.speakers(),
         }
     }
 
@@ -75,4 +276,110 @@ impl AudioRef {
     pub fn channels(&self) -> usize {
         unsafe { audio_output_get_channels(self.pointer) }
     }
+
+    pub fn speakers(&self) -> SpeakerLayout {
+        unsafe {
+            audio_output_get_info(self.pointer)
+                .as_ref()
+                .and_then(|info| SpeakerLayout::from_raw(info.speakers).ok())
+                .unwrap_or(SpeakerLayout::Unknown)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn raw_audio(planes: &mut [Vec<f32>], frames: u32) -> obs_audio_data {
+        let mut data = [std::ptr::null_mut(); MAX_AV_PLANES as usize];
+        for (data, plane) in data.iter_mut().zip(planes.iter_mut()) {
+            *data = plane.as_mut_ptr() as *mut u8;
+        }
+        obs_audio_data {
+            data,
+            frames,
+            timestamp: 0,
+        }
+    }
+
+    #[test]
+    fn interleave_round_trip() {
+        let left = [1., 2., 3.];
+        let right = [-1., -2., -3.];
+        let mut interleaved = [0.; 6];
+        assert_eq!(interleave(&[&left, &right], &mut interleaved), 3);
+        assert_eq!(interleaved, [1., -1., 2., -2., 3., -3.]);
+
+        let (mut a, mut b) = ([0.; 3], [0.; 3]);
+        assert_eq!(deinterleave(&interleaved, &mut [&mut a, &mut b]), 3);
+        assert_eq!(a, left);
+        assert_eq!(b, right);
+    }
+
+    #[test]
+    fn interleave_uneven_channels() {
+        // stops at the shortest channel and leaves the rest of `out` alone
+        let mut out = [9.; 8];
+        assert_eq!(interleave(&[&[1., 2., 3.], &[4., 5.]], &mut out), 2);
+        assert_eq!(out, [1., 4., 2., 5., 9., 9., 9., 9.]);
+
+        let (mut a, mut b) = ([0.; 3], [0.; 1]);
+        assert_eq!(deinterleave(&[1., 2., 3., 4.], &mut [&mut a, &mut b]), 1);
+        assert_eq!(a, [1., 0., 0.]);
+        assert_eq!(b, [2.]);
+
+        assert_eq!(interleave(&[], &mut out), 0);
+        assert_eq!(deinterleave(&[1.], &mut []), 0);
+    }
+
+    #[test]
+    fn interleave_short_buffers() {
+        // only whole frames are copied
+        let mut out = [0.; 5];
+        assert_eq!(interleave(&[&[1., 2., 3.], &[4., 5., 6.]], &mut out), 2);
+        assert_eq!(out, [1., 4., 2., 5., 0.]);
+
+        let (mut a, mut b) = ([0.; 3], [0.; 3]);
+        assert_eq!(deinterleave(&[1., 2., 3.], &mut [&mut a, &mut b]), 1);
+        assert_eq!(a, [1., 0., 0.]);
+        assert_eq!(b, [2., 0., 0.]);
+    }
+
+    #[test]
+    fn frame_channels() {
+        let mut planes = vec![vec![1., 2.], vec![3., 4.], vec![5., 6.]];
+        let mut raw = raw_audio(&mut planes, 2);
+
+        let frame = AudioFrame::from_raw_with_channels(&mut raw, 3);
+        assert_eq!(frame.channels(), 3);
+        assert_eq!(frame.to_interleaved(), [1., 3., 5., 2., 4., 6.]);
+
+        // fewer channels than planes
+        let frame = AudioFrame::from_raw_with_channels(&mut raw, 2);
+        assert_eq!(frame.channels(), 2);
+        assert_eq!(frame.channel(2), None);
+
+        // planes after the first null one are not counted
+        raw.data[1] = std::ptr::null_mut();
+        let mut frame = AudioFrame::from_raw_with_channels(&mut raw, 3);
+        assert_eq!(frame.channels(), 1);
+        assert_eq!(frame.iter_channels().count(), 1);
+        assert_eq!(frame.iter_channels_mut().count(), 1);
+        assert_eq!(frame.channel(0), Some(&[1., 2.][..]));
+        assert_eq!(frame.channel(2), None);
+
+        // more channels than planes exist
+        let frame = AudioFrame::from_raw_with_channels(&mut raw, 100);
+        assert_eq!(frame.channels(), 1);
+    }
+
+    #[test]
+    fn frame_deinterleave() {
+        let mut planes = vec![vec![0.; 2], vec![0.; 2]];
+        let mut raw = raw_audio(&mut planes, 2);
+        let mut frame = AudioFrame::from_raw_with_channels(&mut raw, 2);
+        assert_eq!(frame.deinterleave_from(&[1., 2., 3., 4., 5.]), 2);
+        assert_eq!(planes, [[1., 3.], [2., 4.]]);
+    }
 }
@@ -0,0 +1,690 @@
+//! Conversion between [`VideoFrame`]s of any [`VideoFormat`] and RGBA, either
+//! packed 8 bit or planar `f32`.
+//!
+//! YUV formats are converted with a [`ColorMatrix`] and [`ColorRange`], which
+//! should match the frame, e.g. from the source frame's metadata or the
+//! output's video settings.
+//!
+//! ```ignore
+//! let converter = Converter::new(ColorMatrix::Bt709, ColorRange::Limited);
+//! if let Some(frame) = video.frame() {
+//!     let mut rgba = vec![0; frame.width() as usize * frame.height() as usize * 4];
+//!     converter.to_rgba8(&frame, &mut rgba);
+//! }
+//! ```
+
+use super::{VideoFormat, VideoFrame};
+
+/// Coefficients used to convert between RGB and YUV.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ColorMatrix {
+    Bt601,
+    Bt709,
+    Bt2020,
+}
+
+impl ColorMatrix {
+    /// The red and blue luma coefficients, `Kr` and `Kb`.
+    pub fn coefficients(&self) -> (f32, f32) {
+        match self {
+            ColorMatrix::Bt601 => (0.299, 0.114),
+            ColorMatrix::Bt709 => (0.2126, 0.0722),
+            ColorMatrix::Bt2020 => (0.2627, 0.0593),
+        }
+    }
+}
+
+/// Range of YUV code values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ColorRange {
+    /// The full range of values, e.g. 0-255 for 8 bit.
+    Full,
+    /// Studio swing, e.g. 16-235 for luma and 16-240 for chroma at 8 bit.
+    Limited,
+}
+
+/// An image as separate red, green, blue and alpha planes, with values
+/// between 0 and 1.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlanarF32 {
+    pub width: u32,
+    pub height: u32,
+    pub r: Vec<f32>,
+    pub g: Vec<f32>,
+    pub b: Vec<f32>,
+    pub a: Vec<f32>,
+}
+
+impl PlanarF32 {
+    /// A transparent black image.
+    pub fn new(width: u32, height: u32) -> Self {
+        let len = width as usize * height as usize;
+        Self {
+            width,
+            height,
+            r: vec![0.; len],
+            g: vec![0.; len],
+            b: vec![0.; len],
+            a: vec![0.; len],
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.width as usize * self.height as usize
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Rgb,
+    Yuv,
+}
+
+/// Where a channel is stored: sample `x` of row `y` is element
+/// `(x >> sx) * step + offset` of row `y >> sy` of `plane`.
+#[derive(Clone, Copy)]
+struct Channel {
+    plane: usize,
+    step: usize,
+    offset: usize,
+    sx: u32,
+    sy: u32,
+}
+
+const fn channel(plane: usize, step: usize, offset: usize, sx: u32, sy: u32) -> Option<Channel> {
+    Some(Channel {
+        plane,
+        step,
+        offset,
+        sx,
+        sy,
+    })
+}
+
+struct Layout {
+    kind: Kind,
+    /// Significant bits per sample, samples above 8 bits are stored as
+    /// little endian `u16`.
+    bits: u32,
+    /// Bits the sample is shifted left by within its `u16`.
+    shift: u32,
+    /// Red, green, blue and alpha, or luma, Cb, Cr and alpha. Missing chroma
+    /// is neutral and missing alpha is opaque.
+    channels: [Option<Channel>; 4],
+}
+
+impl Layout {
+    fn new(format: VideoFormat) -> Option<Self> {
+        use VideoFormat::*;
+        let (kind, bits, shift, channels) = match format {
+            VideoFormat::None => return Option::None,
+            RGBA => (
+                Kind::Rgb,
+                8,
+                0,
+                [
+                    channel(0, 4, 0, 0, 0),
+                    channel(0, 4, 1, 0, 0),
+                    channel(0, 4, 2, 0, 0),
+                    channel(0, 4, 3, 0, 0),
+                ],
+            ),
+            BGRA | BGRX => (
+                Kind::Rgb,
+                8,
+                0,
+                [
+                    channel(0, 4, 2, 0, 0),
+                    channel(0, 4, 1, 0, 0),
+                    channel(0, 4, 0, 0, 0),
+                    if format == BGRA {
+                        channel(0, 4, 3, 0, 0)
+                    } else {
+                        Option::None
+                    },
+                ],
+            ),
+            BGR3 => (
+                Kind::Rgb,
+                8,
+                0,
+                [
+                    channel(0, 3, 2, 0, 0),
+                    channel(0, 3, 1, 0, 0),
+                    channel(0, 3, 0, 0, 0),
+                    Option::None,
+                ],
+            ),
+            Y800 => (
+                Kind::Yuv,
+                8,
+                0,
+                [
+                    channel(0, 1, 0, 0, 0),
+                    Option::None,
+                    Option::None,
+                    Option::None,
+                ],
+            ),
+            I420 | I422 | I444 | I40A | I42A | YUVA => {
+                let (sx, sy) = match format {
+                    I420 | I40A => (1, 1),
+                    I422 | I42A => (1, 0),
+                    _ => (0, 0),
+                };
+                let alpha = matches!(format, I40A | I42A | YUVA);
+                (
+                    Kind::Yuv,
+                    8,
+                    0,
+                    [
+                        channel(0, 1, 0, 0, 0),
+                        channel(1, 1, 0, sx, sy),
+                        channel(2, 1, 0, sx, sy),
+                        if alpha {
+                            channel(3, 1, 0, 0, 0)
+                        } else {
+                            Option::None
+                        },
+                    ],
+                )
+            }
+            NV12 | P010 => (
+                Kind::Yuv,
+                if format == NV12 { 8 } else { 10 },
+                if format == NV12 { 0 } else { 6 },
+                [
+                    channel(0, 1, 0, 0, 0),
+                    channel(1, 2, 0, 1, 1),
+                    channel(1, 2, 1, 1, 1),
+                    Option::None,
+                ],
+            ),
+            YUY2 | YVYU | UYVY => {
+                // two pixels share four bytes
+                let (y, u, v) = match format {
+                    YUY2 => (0, 1, 3),
+                    YVYU => (0, 3, 1),
+                    _ => (1, 0, 2),
+                };
+                (
+                    Kind::Yuv,
+                    8,
+                    0,
+                    [
+                        channel(0, 2, y, 0, 0),
+                        channel(0, 4, u, 1, 0),
+                        channel(0, 4, v, 1, 0),
+                        Option::None,
+                    ],
+                )
+            }
+            // stored as a little endian 0xAAYYUUVV word
+            AYUV => (
+                Kind::Yuv,
+                8,
+                0,
+                [
+                    channel(0, 4, 2, 0, 0),
+                    channel(0, 4, 1, 0, 0),
+                    channel(0, 4, 0, 0, 0),
+                    channel(0, 4, 3, 0, 0),
+                ],
+            ),
+            I010 | I210 | I412 | YA2L => {
+                let (bits, sx, sy) = match format {
+                    I010 => (10, 1, 1),
+                    I210 => (10, 1, 0),
+                    _ => (12, 0, 0),
+                };
+                (
+                    Kind::Yuv,
+                    bits,
+                    0,
+                    [
+                        channel(0, 1, 0, 0, 0),
+                        channel(1, 1, 0, sx, sy),
+                        channel(2, 1, 0, sx, sy),
+                        if format == YA2L {
+                            channel(3, 1, 0, 0, 0)
+                        } else {
+                            Option::None
+                        },
+                    ],
+                )
+            }
+        };
+        Some(Self {
+            kind,
+            bits,
+            shift,
+            channels,
+        })
+    }
+
+    fn max(&self) -> f32 {
+        ((1u32 << self.bits) - 1) as f32
+    }
+
+    /// Scale and offset turning a code value of channel `idx` into a
+    /// normalized value, luma and RGB in 0..1 and chroma in -0.5..0.5.
+    fn normalize(&self, idx: usize, range: ColorRange) -> (f32, f32) {
+        let max = self.max();
+        let chroma = self.kind == Kind::Yuv && (idx == 1 || idx == 2);
+        let luma = self.kind == Kind::Yuv && idx == 0;
+        let scale = (1u32 << (self.bits - 8)) as f32;
+        let half = (1u32 << (self.bits - 1)) as f32;
+        match range {
+            ColorRange::Limited if luma => (1. / (219. * scale), -16. / 219.),
+            ColorRange::Limited if chroma => (1. / (224. * scale), -half / (224. * scale)),
+            _ if chroma => (1. / max, -half / max),
+            _ => (1. / max, 0.),
+        }
+    }
+
+    /// The code value of a missing channel.
+    fn default_value(&self, idx: usize) -> f32 {
+        match idx {
+            1 | 2 if self.kind == Kind::Yuv => (1u32 << (self.bits - 1)) as f32,
+            _ => self.max(),
+        }
+    }
+}
+
+fn read_channel(
+    frame: &VideoFrame<'_>,
+    layout: &Layout,
+    channel: &Channel,
+    (scale, offset): (f32, f32),
+    out: &mut [f32],
+) -> Option<()> {
+    let plane = frame.plane(channel.plane)?;
+    let width = frame.width() as usize;
+    if width == 0 {
+        return Some(());
+    }
+    for (y, out) in out.chunks_exact_mut(width).enumerate() {
+        let row = plane.row(y as u32 >> channel.sy);
+        if layout.bits > 8 {
+            for (x, out) in out.iter_mut().enumerate() {
+                let i = ((x >> channel.sx) * channel.step + channel.offset) * 2;
+                let value = u16::from_le_bytes([row[i], row[i + 1]]) >> layout.shift;
+                *out = value as f32 * scale + offset;
+            }
+        } else {
+            for (x, out) in out.iter_mut().enumerate() {
+                let i = (x >> channel.sx) * channel.step + channel.offset;
+                *out = row[i] as f32 * scale + offset;
+            }
+        }
+    }
+    Some(())
+}
+
+fn write_channel(
+    frame: &mut VideoFrame<'_>,
+    layout: &Layout,
+    channel: &Channel,
+    (scale, offset): (f32, f32),
+    src: &[f32],
+) -> Option<()> {
+    let (width, height) = (frame.width() as usize, frame.height() as usize);
+    let max = layout.max();
+    let mut plane = frame.plane_mut(channel.plane)?;
+    let (block_x, block_y) = (1usize << channel.sx, 1usize << channel.sy);
+
+    for cy in 0..height.div_ceil(block_y) {
+        let rows = cy * block_y..((cy + 1) * block_y).min(height);
+        let row = plane.row(cy as u32);
+        for cx in 0..width.div_ceil(block_x) {
+            let columns = cx * block_x..((cx + 1) * block_x).min(width);
+            // average the pixels covered by subsampled chroma
+            let mut sum = 0.;
+            for y in rows.clone() {
+                sum += src[y * width + columns.start..y * width + columns.end]
+                    .iter()
+                    .sum::<f32>();
+            }
+            let value = sum / (rows.len() * columns.len()) as f32;
+            let code = ((value - offset) / scale).round().clamp(0., max) as u16;
+
+            let i = cx * channel.step + channel.offset;
+            if layout.bits > 8 {
+                row[i * 2..i * 2 + 2].copy_from_slice(&(code << layout.shift).to_le_bytes());
+            } else {
+                row[i] = code as u8;
+            }
+        }
+    }
+    Some(())
+}
+
+/// Converts frames to and from RGBA using a given matrix and range.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Converter {
+    matrix: ColorMatrix,
+    range: ColorRange,
+    kr: f32,
+    kg: f32,
+    kb: f32,
+}
+
+impl Converter {
+    pub fn new(matrix: ColorMatrix, range: ColorRange) -> Self {
+        let (kr, kb) = matrix.coefficients();
+        Self {
+            matrix,
+            range,
+            kr,
+            kg: 1. - kr - kb,
+            kb,
+        }
+    }
+
+    pub fn matrix(&self) -> ColorMatrix {
+        self.matrix
+    }
+
+    pub fn range(&self) -> ColorRange {
+        self.range
+    }
+
+    /// Whether `format` can be converted.
+    pub fn supports(format: VideoFormat) -> bool {
+        Layout::new(format).is_some()
+    }
+
+    /// Convert `frame` to planar RGBA, clamped to 0..1.
+    ///
+    /// Returns `None` if the format is not supported.
+    pub fn to_planar(&self, frame: &VideoFrame<'_>) -> Option<PlanarF32> {
+        let layout = Layout::new(frame.format())?;
+        let mut out = PlanarF32::new(frame.width(), frame.height());
+
+        let PlanarF32 { r, g, b, a, .. } = &mut out;
+        for (idx, plane) in [&mut *r, &mut *g, &mut *b, &mut *a].iter_mut().enumerate() {
+            let normalize = layout.normalize(idx, self.range);
+            match &layout.channels[idx] {
+                Some(channel) => read_channel(frame, &layout, channel, normalize, plane)?,
+                None => {
+                    let value = layout.default_value(idx) * normalize.0 + normalize.1;
+                    plane.iter_mut().for_each(|v| *v = value);
+                }
+            }
+        }
+
+        if layout.kind == Kind::Yuv {
+            // planes hold luma, Cb and Cr at this point
+            let rv = 2. * (1. - self.kr);
+            let bu = 2. * (1. - self.kb);
+            let gu = -bu * self.kb / self.kg;
+            let gv = -rv * self.kr / self.kg;
+            for ((r, g), b) in r.iter_mut().zip(g.iter_mut()).zip(b.iter_mut()) {
+                let (y, cb, cr) = (*r, *g, *b);
+                *r = y + rv * cr;
+                *g = y + gu * cb + gv * cr;
+                *b = y + bu * cb;
+            }
+        }
+        for plane in [r, g, b, a] {
+            plane.iter_mut().for_each(|v| *v = v.clamp(0., 1.));
+        }
+        Some(out)
+    }
+
+    /// Write `image` into `frame`, which must have the same size.
+    ///
+    /// Returns `false` if the format is not supported or the sizes differ.
+    pub fn from_planar(&self, image: &PlanarF32, frame: &mut VideoFrame<'_>) -> bool {
+        let layout = match Layout::new(frame.format()) {
+            Some(layout) => layout,
+            None => return false,
+        };
+        if image.width != frame.width() || image.height != frame.height() {
+            return false;
+        }
+
+        let yuv;
+        let planes = match layout.kind {
+            Kind::Rgb => [&image.r, &image.g, &image.b, &image.a],
+            Kind::Yuv => {
+                let len = image.len();
+                let (mut y, mut cb, mut cr) = (vec![0.; len], vec![0.; len], vec![0.; len]);
+                let bu = 2. * (1. - self.kb);
+                let rv = 2. * (1. - self.kr);
+                for i in 0..len {
+                    let (r, g, b) = (image.r[i], image.g[i], image.b[i]);
+                    let luma = self.kr * r + self.kg * g + self.kb * b;
+                    y[i] = luma;
+                    cb[i] = (b - luma) / bu;
+                    cr[i] = (r - luma) / rv;
+                }
+                yuv = [y, cb, cr];
+                [&yuv[0], &yuv[1], &yuv[2], &image.a]
+            }
+        };
+
+        for (idx, src) in planes.iter().enumerate() {
+            if let Some(channel) = &layout.channels[idx] {
+                let normalize = layout.normalize(idx, self.range);
+                if write_channel(frame, &layout, channel, normalize, src).is_none() {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    /// Convert `frame` to tightly packed 8 bit RGBA in `out`, which must hold
+    /// `width * height * 4` bytes.
+    ///
+    /// Returns `false` if the format is not supported or `out` is too small.
+    pub fn to_rgba8(&self, frame: &VideoFrame<'_>, out: &mut [u8]) -> bool {
+        let len = frame.width() as usize * frame.height() as usize;
+        if out.len() < len * 4 {
+            return false;
+        }
+        let image = match self.to_planar(frame) {
+            Some(image) => image,
+            None => return false,
+        };
+        for (i, px) in out.chunks_exact_mut(4).take(len).enumerate() {
+            px[0] = (image.r[i] * 255.).round() as u8;
+            px[1] = (image.g[i] * 255.).round() as u8;
+            px[2] = (image.b[i] * 255.).round() as u8;
+            px[3] = (image.a[i] * 255.).round() as u8;
+        }
+        true
+    }
+
+    /// Write tightly packed 8 bit RGBA from `rgba` into `frame`.
+    ///
+    /// Returns `false` if the format is not supported or `rgba` is too small.
+    pub fn from_rgba8(&self, rgba: &[u8], frame: &mut VideoFrame<'_>) -> bool {
+        let mut image = PlanarF32::new(frame.width(), frame.height());
+        let len = image.len();
+        if rgba.len() < len * 4 {
+            return false;
+        }
+        for (i, px) in rgba.chunks_exact(4).take(len).enumerate() {
+            image.r[i] = px[0] as f32 / 255.;
+            image.g[i] = px[1] as f32 / 255.;
+            image.b[i] = px[2] as f32 / 255.;
+            image.a[i] = px[3] as f32 / 255.;
+        }
+        self.from_planar(&image, frame)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::media::VideoInfo;
+
+    fn info(format: VideoFormat, width: u32, height: u32) -> VideoInfo {
+        VideoInfo {
+            width,
+            height,
+            frame_rate: 30.,
+            format: Some(format),
+        }
+    }
+
+    /// Encode a single color into a 2x2 frame and return the first sample of
+    /// each plane.
+    fn encode(converter: Converter, format: VideoFormat, rgba: [u8; 4]) -> Vec<u8> {
+        let info = info(format, 2, 2);
+        let mut buffer = vec![0; info.frame_size().total()];
+        let mut frame = VideoFrame::from_buffer(&info, &mut buffer).unwrap();
+        assert!(converter.from_rgba8(&rgba.repeat(4), &mut frame));
+        drop(frame);
+        buffer
+    }
+
+    fn decode(converter: Converter, format: VideoFormat, buffer: &mut [u8]) -> [u8; 4] {
+        let info = info(format, 2, 2);
+        let frame = VideoFrame::from_buffer(&info, buffer).unwrap();
+        let mut out = [0; 16];
+        assert!(converter.to_rgba8(&frame, &mut out));
+        [out[0], out[1], out[2], out[3]]
+    }
+
+    #[test]
+    fn bt709_limited_known_values() {
+        let converter = Converter::new(ColorMatrix::Bt709, ColorRange::Limited);
+        // I444 planes are 4 bytes each for a 2x2 frame
+        let yuv = |rgba| {
+            let buffer = encode(converter, VideoFormat::I444, rgba);
+            [buffer[0], buffer[4], buffer[8]]
+        };
+        assert_eq!(yuv([255, 255, 255, 255]), [235, 128, 128]);
+        assert_eq!(yuv([0, 0, 0, 255]), [16, 128, 128]);
+        assert_eq!(yuv([255, 0, 0, 255]), [63, 102, 240]);
+        assert_eq!(yuv([0, 255, 0, 255]), [173, 42, 26]);
+        assert_eq!(yuv([0, 0, 255, 255]), [32, 240, 118]);
+    }
+
+    #[test]
+    fn bt601_known_values() {
+        let limited = Converter::new(ColorMatrix::Bt601, ColorRange::Limited);
+        let buffer = encode(limited, VideoFormat::I420, [255, 0, 0, 255]);
+        // 4 luma samples followed by one Cb and one Cr
+        assert_eq!(buffer, vec![81, 81, 81, 81, 90, 240]);
+
+        let full = Converter::new(ColorMatrix::Bt601, ColorRange::Full);
+        let buffer = encode(full, VideoFormat::I420, [255, 0, 0, 255]);
+        assert_eq!(buffer, vec![76, 76, 76, 76, 85, 255]);
+    }
+
+    #[test]
+    fn bt2020_known_values() {
+        let converter = Converter::new(ColorMatrix::Bt2020, ColorRange::Limited);
+        let buffer = encode(converter, VideoFormat::NV12, [0, 255, 0, 255]);
+        assert_eq!(buffer, vec![164, 164, 164, 164, 47, 25]);
+    }
+
+    #[test]
+    fn ten_bit_formats() {
+        let converter = Converter::new(ColorMatrix::Bt709, ColorRange::Limited);
+        let white = [255, 255, 255, 255];
+
+        // luma 940 and chroma 512, shifted into the high bits
+        let buffer = encode(converter, VideoFormat::P010, white);
+        assert_eq!(&buffer[..2], &(940u16 << 6).to_le_bytes());
+        assert_eq!(&buffer[8..10], &(512u16 << 6).to_le_bytes());
+
+        let buffer = encode(converter, VideoFormat::I010, white);
+        assert_eq!(&buffer[..2], &940u16.to_le_bytes());
+        assert_eq!(&buffer[8..10], &512u16.to_le_bytes());
+    }
+
+    #[test]
+    fn packed_422_order() {
+        let converter = Converter::new(ColorMatrix::Bt709, ColorRange::Limited);
+        let red = [255, 0, 0, 255];
+        assert_eq!(
+            &encode(converter, VideoFormat::YUY2, red)[..4],
+            &[63, 102, 63, 240]
+        );
+        assert_eq!(
+            &encode(converter, VideoFormat::YVYU, red)[..4],
+            &[63, 240, 63, 102]
+        );
+        assert_eq!(
+            &encode(converter, VideoFormat::UYVY, red)[..4],
+            &[102, 63, 240, 63]
+        );
+    }
+
+    #[test]
+    fn decodes_known_values() {
+        let converter = Converter::new(ColorMatrix::Bt709, ColorRange::Limited);
+        let mut buffer = [235, 235, 235, 235, 128, 128];
+        assert_eq!(
+            decode(converter, VideoFormat::I420, &mut buffer),
+            [255, 255, 255, 255]
+        );
+
+        // red quantized to 8 bit YUV comes back within one step
+        let mut buffer = [63, 63, 63, 63, 102, 240];
+        let [r, g, b, a] = decode(converter, VideoFormat::I420, &mut buffer);
+        assert_eq!((r, a), (255, 255));
+        assert!(g <= 1 && b <= 1, "{} {}", g, b);
+
+        let mut buffer = [16, 16, 16, 16, 128, 128];
+        assert_eq!(
+            decode(converter, VideoFormat::NV12, &mut buffer),
+            [0, 0, 0, 255]
+        );
+
+        let mut buffer = [3, 2, 1, 0].repeat(4);
+        assert_eq!(
+            decode(converter, VideoFormat::BGRX, &mut buffer),
+            [1, 2, 3, 255]
+        );
+    }
+
+    #[test]
+    fn round_trips() {
+        let colors = [
+            [255, 255, 255, 255],
+            [0, 0, 0, 255],
+            [200, 30, 90, 128],
+            [12, 240, 180, 0],
+        ];
+        let formats = [
+            VideoFormat::RGBA,
+            VideoFormat::BGRA,
+            VideoFormat::I444,
+            VideoFormat::YUVA,
+            VideoFormat::AYUV,
+            VideoFormat::I420,
+            VideoFormat::NV12,
+            VideoFormat::YUY2,
+            VideoFormat::P010,
+            VideoFormat::I412,
+        ];
+        for matrix in [ColorMatrix::Bt601, ColorMatrix::Bt709, ColorMatrix::Bt2020] {
+            for range in [ColorRange::Full, ColorRange::Limited] {
+                let converter = Converter::new(matrix, range);
+                for &format in &formats {
+                    for color in colors {
+                        let mut buffer = encode(converter, format, color);
+                        let out = decode(converter, format, &mut buffer);
+                        for c in 0..3 {
+                            assert!(
+                                (out[c] as i32 - color[c] as i32).abs() <= 2,
+                                "{:?} {:?} {:?}: {:?} != {:?}",
+                                format,
+                                matrix,
+                                range,
+                                out,
+                                color
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
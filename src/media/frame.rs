@@ -0,0 +1,641 @@
+use std::marker::PhantomData;
+
+use obs_sys::MAX_AV_PLANES;
+
+use super::{VideoFormat, VideoInfo};
+
+/// Size of a single plane of a video frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlaneLayout {
+    /// Width of the plane in samples, e.g. half the frame width for
+    /// subsampled chroma planes.
+    pub width: u32,
+    /// Number of rows in the plane.
+    pub height: u32,
+    /// Bytes of pixel data in each row, not including any padding.
+    pub row_bytes: usize,
+}
+
+impl VideoFormat {
+    /// The planes of a `width` by `height` frame in this format, matching the
+    /// sizes in [`VideoInfo::frame_size`].
+    pub fn plane_layout(&self, width: u32, height: u32) -> Vec<PlaneLayout> {
+        use VideoFormat::*;
+        let half_width = width.div_ceil(2);
+        let half_height = height.div_ceil(2);
+        let plane = |width: u32, height: u32, bytes: u32| PlaneLayout {
+            width,
+            height,
+            row_bytes: width as usize * bytes as usize,
+        };
+        let luma = plane(width, height, 1);
+        let luma16 = plane(width, height, 2);
+
+        match self {
+            VideoFormat::None => vec![],
+            I420 => vec![
+                luma,
+                plane(half_width, half_height, 1),
+                plane(half_width, half_height, 1),
+            ],
+            NV12 => vec![luma, plane(half_width, half_height, 2)],
+            // packed 4:2:2 stores two pixels in four bytes
+            YVYU | YUY2 | UYVY => vec![PlaneLayout {
+                width,
+                height,
+                row_bytes: half_width as usize * 4,
+            }],
+            RGBA | BGRA | BGRX | AYUV => vec![plane(width, height, 4)],
+            Y800 => vec![luma],
+            I444 => vec![luma; 3],
+            I412 => vec![luma16; 3],
+            BGR3 => vec![plane(width, height, 3)],
+            I422 => vec![
+                luma,
+                plane(half_width, height, 1),
+                plane(half_width, height, 1),
+            ],
+            I210 => vec![
+                luma16,
+                plane(half_width, height, 2),
+                plane(half_width, height, 2),
+            ],
+            I40A => vec![
+                luma,
+                plane(half_width, half_height, 1),
+                plane(half_width, half_height, 1),
+                luma,
+            ],
+            I42A => vec![
+                luma,
+                plane(half_width, height, 1),
+                plane(half_width, height, 1),
+                luma,
+            ],
+            YUVA => vec![luma; 4],
+            YA2L => vec![luma16; 4],
+            I010 => vec![
+                luma16,
+                plane(half_width, half_height, 2),
+                plane(half_width, half_height, 2),
+            ],
+            P010 => vec![luma16, plane(half_width, half_height, 4)],
+        }
+    }
+}
+
+/// A single plane of a [`VideoFrame`].
+#[derive(Clone, Copy)]
+pub struct VideoPlane<'a> {
+    data: &'a [u8],
+    layout: PlaneLayout,
+    stride: usize,
+}
+
+impl<'a> VideoPlane<'a> {
+    pub fn width(&self) -> u32 {
+        self.layout.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.layout.height
+    }
+
+    /// Bytes between the start of two rows, also called the linesize.
+    pub fn stride(&self) -> usize {
+        self.stride
+    }
+
+    pub fn layout(&self) -> PlaneLayout {
+        self.layout
+    }
+
+    /// The whole plane, including any padding between rows.
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// The pixel data of row `y`, without padding.
+    ///
+    /// # Panics
+    ///
+    /// If `y` is not less than [`height`](Self::height).
+    pub fn row(&self, y: u32) -> &'a [u8] {
+        let start = y as usize * self.stride;
+        &self.data[start..start + self.layout.row_bytes]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &'a [u8]> {
+        let row_bytes = self.layout.row_bytes;
+        self.data
+            .chunks(self.stride.max(1))
+            .take(self.layout.height as usize)
+            .map(move |row| &row[..row_bytes])
+    }
+}
+
+/// A single plane of a [`VideoFrame`] which can be written to.
+pub struct VideoPlaneMut<'a> {
+    data: &'a mut [u8],
+    layout: PlaneLayout,
+    stride: usize,
+}
+
+impl VideoPlaneMut<'_> {
+    pub fn width(&self) -> u32 {
+        self.layout.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.layout.height
+    }
+
+    /// Bytes between the start of two rows, also called the linesize.
+    pub fn stride(&self) -> usize {
+        self.stride
+    }
+
+    pub fn layout(&self) -> PlaneLayout {
+        self.layout
+    }
+
+    /// The whole plane, including any padding between rows.
+    pub fn data(&mut self) -> &mut [u8] {
+        self.data
+    }
+
+    /// The pixel data of row `y`, without padding.
+    ///
+    /// # Panics
+    ///
+    /// If `y` is not less than [`height`](Self::height).
+    pub fn row(&mut self, y: u32) -> &mut [u8] {
+        let start = y as usize * self.stride;
+        &mut self.data[start..start + self.layout.row_bytes]
+    }
+
+    pub fn rows(&mut self) -> impl Iterator<Item = &mut [u8]> {
+        let row_bytes = self.layout.row_bytes;
+        self.data
+            .chunks_mut(self.stride.max(1))
+            .take(self.layout.height as usize)
+            .map(move |row| &mut row[..row_bytes])
+    }
+}
+
+/// A pixel of a packed RGB frame, regardless of the channel order in memory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Rgba8 {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+/// A pixel of an 8 bit planar YUV frame. Subsampled chroma is shared between
+/// neighbouring pixels, and `a` is 255 for formats without alpha.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Yuva8 {
+    pub y: u8,
+    pub u: u8,
+    pub v: u8,
+    pub a: u8,
+}
+
+#[derive(Clone, Copy)]
+enum PackedOrder {
+    Rgba,
+    Bgra,
+    Bgrx,
+}
+
+impl PackedOrder {
+    fn read(self, p: &[u8]) -> Rgba8 {
+        match self {
+            PackedOrder::Rgba => Rgba8 {
+                r: p[0],
+                g: p[1],
+                b: p[2],
+                a: p[3],
+            },
+            PackedOrder::Bgra => Rgba8 {
+                r: p[2],
+                g: p[1],
+                b: p[0],
+                a: p[3],
+            },
+            PackedOrder::Bgrx => Rgba8 {
+                r: p[2],
+                g: p[1],
+                b: p[0],
+                a: 255,
+            },
+        }
+    }
+
+    fn write(self, p: &mut [u8], px: Rgba8) {
+        match self {
+            PackedOrder::Rgba => p.copy_from_slice(&[px.r, px.g, px.b, px.a]),
+            PackedOrder::Bgra => p.copy_from_slice(&[px.b, px.g, px.r, px.a]),
+            PackedOrder::Bgrx => p[..3].copy_from_slice(&[px.b, px.g, px.r]),
+        }
+    }
+}
+
+struct RawPlane {
+    data: *mut u8,
+    len: usize,
+    layout: PlaneLayout,
+    stride: usize,
+}
+
+/// A view of the planes of a video frame, sized according to its format.
+///
+/// Obtained from [`VideoDataSourceContext::frame`](super::VideoDataSourceContext::frame),
+/// [`VideoDataOutputContext::frame`](super::VideoDataOutputContext::frame) or
+/// over a plain buffer with [`from_buffer`](Self::from_buffer).
+pub struct VideoFrame<'a> {
+    format: VideoFormat,
+    width: u32,
+    height: u32,
+    planes: Vec<RawPlane>,
+    _marker: PhantomData<&'a mut [u8]>,
+}
+
+impl<'a> VideoFrame<'a> {
+    /// # Safety
+    ///
+    /// Each plane of `format` must point to at least `linesize * height`
+    /// bytes, which are valid to read and write for `'a`.
+    pub unsafe fn from_raw(
+        format: VideoFormat,
+        width: u32,
+        height: u32,
+        data: &[*mut u8; MAX_AV_PLANES as usize],
+        linesize: &[u32; MAX_AV_PLANES as usize],
+    ) -> Option<Self> {
+        let mut planes = Vec::new();
+        for (i, layout) in format.plane_layout(width, height).into_iter().enumerate() {
+            let stride = linesize[i] as usize;
+            if data[i].is_null() || stride < layout.row_bytes {
+                return None;
+            }
+            planes.push(RawPlane {
+                data: data[i],
+                len: plane_len(&layout, stride),
+                layout,
+                stride,
+            });
+        }
+
+        Some(Self {
+            format,
+            width,
+            height,
+            planes,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Use `buffer` as a frame described by `info`, with the planes packed
+    /// one after another without padding as in [`VideoInfo::frame_size`].
+    ///
+    /// Returns `None` if the format is unknown or `buffer` is too small.
+    pub fn from_buffer(info: &VideoInfo, buffer: &'a mut [u8]) -> Option<Self> {
+        let format = info.format?;
+        let layouts = format.plane_layout(info.width, info.height);
+        let sizes = info.frame_size().plane_sizes();
+        if layouts.len() != sizes.len() || sizes.iter().sum::<usize>() > buffer.len() {
+            return None;
+        }
+
+        let mut planes = Vec::new();
+        let mut rest = buffer;
+        for (layout, size) in layouts.into_iter().zip(sizes) {
+            let (plane, next) = rest.split_at_mut(size);
+            rest = next;
+            let stride = layout.row_bytes;
+            if plane_len(&layout, stride) > size {
+                return None;
+            }
+            planes.push(RawPlane {
+                data: plane.as_mut_ptr(),
+                len: size,
+                layout,
+                stride,
+            });
+        }
+
+        Some(Self {
+            format,
+            width: info.width,
+            height: info.height,
+            planes,
+            _marker: PhantomData,
+        })
+    }
+
+    pub fn format(&self) -> VideoFormat {
+        self.format
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn plane_count(&self) -> usize {
+        self.planes.len()
+    }
+
+    pub fn plane(&self, idx: usize) -> Option<VideoPlane<'_>> {
+        let plane = self.planes.get(idx)?;
+        Some(VideoPlane {
+            data: unsafe { std::slice::from_raw_parts(plane.data, plane.len) },
+            layout: plane.layout,
+            stride: plane.stride,
+        })
+    }
+
+    pub fn plane_mut(&mut self, idx: usize) -> Option<VideoPlaneMut<'_>> {
+        let plane = self.planes.get(idx)?;
+        Some(VideoPlaneMut {
+            data: unsafe { std::slice::from_raw_parts_mut(plane.data, plane.len) },
+            layout: plane.layout,
+            stride: plane.stride,
+        })
+    }
+
+    fn packed_order(&self) -> Option<PackedOrder> {
+        match self.format {
+            VideoFormat::RGBA => Some(PackedOrder::Rgba),
+            VideoFormat::BGRA => Some(PackedOrder::Bgra),
+            VideoFormat::BGRX => Some(PackedOrder::Bgrx),
+            _ => None,
+        }
+    }
+
+    /// Iterate over the pixels of an `RGBA`, `BGRA` or `BGRX` frame row by
+    /// row. Returns `None` for other formats.
+    pub fn rgba_pixels(&self) -> Option<impl Iterator<Item = Rgba8> + '_> {
+        let order = self.packed_order()?;
+        let plane = self.plane(0)?;
+        Some(
+            plane
+                .rows()
+                .flat_map(move |row| row.chunks_exact(4).map(move |p| order.read(p))),
+        )
+    }
+
+    /// Replace every pixel of an `RGBA`, `BGRA` or `BGRX` frame with the
+    /// result of `func`. The alpha of `BGRX` frames is left untouched.
+    ///
+    /// Returns `false` for other formats.
+    pub fn map_rgba_pixels<F: FnMut(Rgba8) -> Rgba8>(&mut self, mut func: F) -> bool {
+        let order = match self.packed_order() {
+            Some(order) => order,
+            None => return false,
+        };
+        let mut plane = match self.plane_mut(0) {
+            Some(plane) => plane,
+            None => return false,
+        };
+        for row in plane.rows() {
+            for p in row.chunks_exact_mut(4) {
+                order.write(p, func(order.read(p)));
+            }
+        }
+        true
+    }
+
+    /// Iterate over the pixels of an 8 bit planar YUV frame (`I420`, `NV12`,
+    /// `I422`, `I444`, `I40A`, `I42A` or `YUVA`) row by row. Returns `None`
+    /// for other formats.
+    pub fn yuv_pixels(&self) -> Option<impl Iterator<Item = Yuva8> + '_> {
+        // chroma subsampling as shifts, whether u and v share a plane, and
+        // whether there is an alpha plane
+        let (shift_x, shift_y, interleaved, alpha) = match self.format {
+            VideoFormat::I420 => (1, 1, false, false),
+            VideoFormat::NV12 => (1, 1, true, false),
+            VideoFormat::I422 => (1, 0, false, false),
+            VideoFormat::I444 => (0, 0, false, false),
+            VideoFormat::I40A => (1, 1, false, true),
+            VideoFormat::I42A => (1, 0, false, true),
+            VideoFormat::YUVA => (0, 0, false, true),
+            _ => return None,
+        };
+
+        let luma = self.plane(0)?;
+        let u_plane = self.plane(1)?;
+        let v_plane = if interleaved { u_plane } else { self.plane(2)? };
+        let a_plane = if alpha { Some(self.plane(3)?) } else { None };
+
+        Some((0..luma.height()).flat_map(move |y| {
+            let u_row = u_plane.row(y >> shift_y);
+            let v_row = v_plane.row(y >> shift_y);
+            let a_row = a_plane.map(|plane| plane.row(y));
+            luma.row(y).iter().enumerate().map(move |(x, &l)| {
+                let cx = x >> shift_x;
+                let (u, v) = if interleaved {
+                    (u_row[cx * 2], u_row[cx * 2 + 1])
+                } else {
+                    (u_row[cx], v_row[cx])
+                };
+                Yuva8 {
+                    y: l,
+                    u,
+                    v,
+                    a: a_row.map_or(255, |a| a[x]),
+                }
+            })
+        }))
+    }
+}
+
+fn plane_len(layout: &PlaneLayout, stride: usize) -> usize {
+    match layout.height {
+        0 => 0,
+        height => stride * (height as usize - 1) + layout.row_bytes,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info(format: VideoFormat, width: u32, height: u32) -> VideoInfo {
+        VideoInfo {
+            width,
+            height,
+            frame_rate: 30.,
+            format: Some(format),
+        }
+    }
+
+    fn plane_shape(frame: &VideoFrame, idx: usize) -> (u32, u32, usize, usize) {
+        let plane = frame.plane(idx).unwrap();
+        (
+            plane.width(),
+            plane.height(),
+            plane.stride(),
+            plane.data().len(),
+        )
+    }
+
+    #[test]
+    fn rgba_frame() {
+        let mut buffer: Vec<u8> = (0..3 * 2 * 4).map(|i| i as u8).collect();
+        let frame = VideoFrame::from_buffer(&info(VideoFormat::RGBA, 3, 2), &mut buffer).unwrap();
+        assert_eq!(frame.plane_count(), 1);
+        assert_eq!(plane_shape(&frame, 0), (3, 2, 12, 24));
+
+        let pixels: Vec<Rgba8> = frame.rgba_pixels().unwrap().collect();
+        assert_eq!(pixels.len(), 6);
+        assert_eq!(
+            pixels[0],
+            Rgba8 {
+                r: 0,
+                g: 1,
+                b: 2,
+                a: 3
+            }
+        );
+        assert_eq!(
+            pixels[5],
+            Rgba8 {
+                r: 20,
+                g: 21,
+                b: 22,
+                a: 23
+            }
+        );
+        assert!(frame.yuv_pixels().is_none());
+    }
+
+    #[test]
+    fn i420_frame() {
+        // 5x3 luma, two planes of 3x2 chroma
+        let mut buffer = vec![0u8; 15 + 6 + 6];
+        buffer[..15]
+            .iter_mut()
+            .enumerate()
+            .for_each(|(i, p)| *p = i as u8);
+        buffer[15..21].copy_from_slice(&[100, 101, 102, 103, 104, 105]);
+        buffer[21..].copy_from_slice(&[200, 201, 202, 203, 204, 205]);
+        let frame = VideoFrame::from_buffer(&info(VideoFormat::I420, 5, 3), &mut buffer).unwrap();
+        assert_eq!(frame.plane_count(), 3);
+        assert_eq!(plane_shape(&frame, 0), (5, 3, 5, 15));
+        assert_eq!(plane_shape(&frame, 1), (3, 2, 3, 6));
+        assert_eq!(plane_shape(&frame, 2), (3, 2, 3, 6));
+
+        let pixels: Vec<Yuva8> = frame.yuv_pixels().unwrap().collect();
+        assert_eq!(pixels.len(), 15);
+        assert_eq!(
+            pixels[0],
+            Yuva8 {
+                y: 0,
+                u: 100,
+                v: 200,
+                a: 255
+            }
+        );
+        // the last column and row share the last chroma sample
+        assert_eq!(
+            pixels[14],
+            Yuva8 {
+                y: 14,
+                u: 105,
+                v: 205,
+                a: 255
+            }
+        );
+        assert!(frame.rgba_pixels().is_none());
+    }
+
+    #[test]
+    fn nv12_frame() {
+        let mut buffer = vec![0u8; 15 + 12];
+        buffer[..15]
+            .iter_mut()
+            .enumerate()
+            .for_each(|(i, p)| *p = i as u8);
+        buffer[15..]
+            .iter_mut()
+            .enumerate()
+            .for_each(|(i, p)| *p = 100 + i as u8);
+        let frame = VideoFrame::from_buffer(&info(VideoFormat::NV12, 5, 3), &mut buffer).unwrap();
+        assert_eq!(frame.plane_count(), 2);
+        assert_eq!(plane_shape(&frame, 0), (5, 3, 5, 15));
+        assert_eq!(plane_shape(&frame, 1), (3, 2, 6, 12));
+
+        let pixels: Vec<Yuva8> = frame.yuv_pixels().unwrap().collect();
+        assert_eq!(
+            pixels[0],
+            Yuva8 {
+                y: 0,
+                u: 100,
+                v: 101,
+                a: 255
+            }
+        );
+        assert_eq!(
+            pixels[14],
+            Yuva8 {
+                y: 14,
+                u: 110,
+                v: 111,
+                a: 255
+            }
+        );
+    }
+
+    #[test]
+    fn padded_linesize() {
+        // 2x2 BGRA with rows padded to 16 bytes, the padding is 0xff
+        let mut buffer = [0xffu8; 16 + 8];
+        buffer[..8].copy_from_slice(&[1, 2, 3, 4, 5, 6, 7, 8]);
+        buffer[16..].copy_from_slice(&[9, 10, 11, 12, 13, 14, 15, 16]);
+        let mut data = [std::ptr::null_mut(); MAX_AV_PLANES as usize];
+        let mut linesize = [0; MAX_AV_PLANES as usize];
+        data[0] = buffer.as_mut_ptr();
+        linesize[0] = 16;
+
+        let frame =
+            unsafe { VideoFrame::from_raw(VideoFormat::BGRA, 2, 2, &data, &linesize) }.unwrap();
+        // the last row isn't padded
+        assert_eq!(plane_shape(&frame, 0), (2, 2, 16, 24));
+        let plane = frame.plane(0).unwrap();
+        assert_eq!(plane.row(1), &[9, 10, 11, 12, 13, 14, 15, 16]);
+        assert_eq!(plane.rows().count(), 2);
+
+        let pixels: Vec<Rgba8> = frame.rgba_pixels().unwrap().collect();
+        assert_eq!(pixels.len(), 4);
+        assert_eq!(
+            pixels[0],
+            Rgba8 {
+                r: 3,
+                g: 2,
+                b: 1,
+                a: 4
+            }
+        );
+        assert_eq!(
+            pixels[3],
+            Rgba8 {
+                r: 15,
+                g: 14,
+                b: 13,
+                a: 16
+            }
+        );
+
+        // a linesize shorter than a row is rejected
+        linesize[0] = 7;
+        assert!(
+            unsafe { VideoFrame::from_raw(VideoFormat::BGRA, 2, 2, &data, &linesize) }.is_none()
+        );
+    }
+}
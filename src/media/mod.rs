@@ -1,7 +1,13 @@
 pub mod audio;
+pub mod convert;
+pub mod frame;
+pub mod raw;
+pub mod resample;
 pub mod state;
 pub mod video;
+pub mod volume;
 
 pub use audio::*;
+pub use frame::*;
 pub use state::*;
 pub use video::*;
@@ -0,0 +1,295 @@
+//! Receive the mixed video and audio of OBS without creating an output.
+//!
+//! Each callback stays registered until the subscription returned for it is
+//! dropped.
+//!
+//! ```ignore
+//! let video = raw::add_video_callback(
+//!     Some(VideoConversion::new(VideoFormat::RGBA).with_size(320, 180)),
+//!     move |frame, timestamp| {
+//!         let red: u64 = frame.rgba_pixels().unwrap().map(|p| p.r as u64).sum();
+//!         sender.send((timestamp, red)).ok();
+//!     },
+//! );
+//! let audio = raw::add_audio_callback(0, None, |audio| {
+//!     let peak = audio.iter_channels().flatten().fold(0f32, |a, s| a.max(s.abs()));
+//! });
+//! ```
+
+use std::os::raw::c_void;
+
+use obs_sys::{
+    audio_convert_info, audio_data, audio_format_AUDIO_FORMAT_FLOAT_PLANAR,
+    audio_output_get_channels, obs_add_raw_audio_callback, obs_add_raw_video_callback,
+    obs_audio_data, obs_get_audio, obs_get_video, obs_remove_raw_audio_callback,
+    obs_remove_raw_video_callback, video_data, video_output_get_format, video_output_get_height,
+    video_output_get_width, video_scale_info, MAX_AUDIO_MIXES,
+};
+
+use super::{
+    resample::ResampleInfo, AudioFrame, VideoColorSpace, VideoFormat, VideoFrame, VideoRange,
+};
+
+/// Format to convert raw video to before it is passed to the callback.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VideoConversion {
+    pub format: VideoFormat,
+    /// `0` to keep the width of the video output, independently of `height`.
+    pub width: u32,
+    /// `0` to keep the height of the video output, independently of `width`.
+    pub height: u32,
+    pub range: VideoRange,
+    pub colorspace: VideoColorSpace,
+}
+
+impl VideoConversion {
+    /// Convert to `format`, keeping the size, range and color space of the
+    /// video output.
+    pub fn new(format: VideoFormat) -> Self {
+        Self {
+            format,
+            width: 0,
+            height: 0,
+            range: VideoRange::Default,
+            colorspace: VideoColorSpace::Default,
+        }
+    }
+
+    /// Scale to `width` by `height`. Either can be `0` to keep that dimension
+    /// of the video output.
+    pub fn with_size(mut self, width: u32, height: u32) -> Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    pub fn with_range(mut self, range: VideoRange) -> Self {
+        self.range = range;
+        self
+    }
+
+    pub fn with_colorspace(mut self, colorspace: VideoColorSpace) -> Self {
+        self.colorspace = colorspace;
+        self
+    }
+
+    fn as_raw(&self) -> video_scale_info {
+        video_scale_info {
+            format: self.format.as_raw(),
+            width: self.width,
+            height: self.height,
+            range: self.range.as_raw(),
+            colorspace: self.colorspace.as_raw(),
+        }
+    }
+}
+
+type VideoCallback = Box<dyn FnMut(&VideoFrame, u64) + Send>;
+
+struct VideoParam {
+    format: VideoFormat,
+    width: u32,
+    height: u32,
+    callback: VideoCallback,
+}
+
+unsafe extern "C" fn raw_video(param: *mut c_void, frame: *mut video_data) {
+    let param = &mut *(param as *mut VideoParam);
+    let frame = match frame.as_ref() {
+        Some(frame) => frame,
+        None => return,
+    };
+    if let Some(video) = VideoFrame::from_raw(
+        param.format,
+        param.width,
+        param.height,
+        &frame.data,
+        &frame.linesize,
+    ) {
+        (param.callback)(&video, frame.timestamp);
+    }
+}
+
+/// Removes the callback added by [`add_video_callback`] when dropped.
+///
+/// The format and size of the frames are resolved when subscribing, and are
+/// not updated if the video is reset with `obs_reset_video`, e.g. after the
+/// canvas size changed. Drop the subscription and subscribe again after a
+/// reset.
+#[must_use = "the callback is removed when the subscription is dropped"]
+pub struct RawVideoSubscription {
+    param: *mut VideoParam,
+}
+
+impl RawVideoSubscription {
+    pub fn format(&self) -> VideoFormat {
+        unsafe { (*self.param).format }
+    }
+
+    /// Width of the frames, as of subscribing.
+    pub fn width(&self) -> u32 {
+        unsafe { (*self.param).width }
+    }
+
+    /// Height of the frames, as of subscribing.
+    pub fn height(&self) -> u32 {
+        unsafe { (*self.param).height }
+    }
+}
+
+impl Drop for RawVideoSubscription {
+    fn drop(&mut self) {
+        unsafe {
+            // libobs holds its input lock while calling back, so once removed
+            // the closure can't be running anymore
+            obs_remove_raw_video_callback(Some(raw_video), self.param as *mut c_void);
+            drop(Box::from_raw(self.param));
+        }
+    }
+}
+
+/// Call `callback` with every frame of the main video output and its
+/// timestamp in nanoseconds, converted to `conversion` if given.
+///
+/// It is called from the video thread. Returns `None` if video isn't
+/// initialized or its format is unknown.
+pub fn add_video_callback<F: FnMut(&VideoFrame, u64) + Send + 'static>(
+    conversion: Option<VideoConversion>,
+    callback: F,
+) -> Option<RawVideoSubscription> {
+    let video = unsafe { obs_get_video() };
+    if video.is_null() {
+        return None;
+    }
+    let (format, mut width, mut height) = unsafe {
+        (
+            VideoFormat::from_raw(video_output_get_format(video)).ok()?,
+            video_output_get_width(video),
+            video_output_get_height(video),
+        )
+    };
+    // libobs fills in zero dimensions with those of the output in the same way
+    let format = match conversion {
+        Some(conversion) => {
+            if conversion.width != 0 {
+                width = conversion.width;
+            }
+            if conversion.height != 0 {
+                height = conversion.height;
+            }
+            conversion.format
+        }
+        None => format,
+    };
+
+    let param = Box::into_raw(Box::new(VideoParam {
+        format,
+        width,
+        height,
+        callback: Box::new(callback),
+    }));
+    let raw = conversion.as_ref().map(VideoConversion::as_raw);
+    unsafe {
+        obs_add_raw_video_callback(
+            raw.as_ref()
+                .map_or(std::ptr::null(), |raw| raw as *const video_scale_info),
+            Some(raw_video),
+            param as *mut c_void,
+        );
+    }
+    Some(RawVideoSubscription { param })
+}
+
+type AudioCallback = Box<dyn FnMut(&AudioFrame) + Send>;
+
+struct AudioParam {
+    mix: usize,
+    channels: usize,
+    callback: AudioCallback,
+}
+
+unsafe extern "C" fn raw_audio(param: *mut c_void, _mix: usize, data: *mut audio_data) {
+    let param = &mut *(param as *mut AudioParam);
+    if data.is_null() {
+        return;
+    }
+    // `audio_data` has the same layout as `obs_audio_data`, and the frame is
+    // only handed out by shared reference so it's never written to
+    let frame = AudioFrame::from_raw_with_channels(data as *mut obs_audio_data, param.channels);
+    (param.callback)(&frame);
+}
+
+/// Removes the callback added by [`add_audio_callback`] when dropped.
+#[must_use = "the callback is removed when the subscription is dropped"]
+pub struct RawAudioSubscription {
+    param: *mut AudioParam,
+}
+
+impl RawAudioSubscription {
+    pub fn mix(&self) -> usize {
+        unsafe { (*self.param).mix }
+    }
+
+    pub fn channels(&self) -> usize {
+        unsafe { (*self.param).channels }
+    }
+}
+
+impl Drop for RawAudioSubscription {
+    fn drop(&mut self) {
+        unsafe {
+            obs_remove_raw_audio_callback(
+                (*self.param).mix,
+                Some(raw_audio),
+                self.param as *mut c_void,
+            );
+            drop(Box::from_raw(self.param));
+        }
+    }
+}
+
+/// Call `callback` with the planar float audio of `mix`, one of the 6
+/// tracks, converted to the rate and layout of `conversion` if given.
+///
+/// It is called from the audio thread. Returns `None` if audio isn't
+/// initialized, `mix` is out of range or the layout of `conversion` is
+/// unknown.
+pub fn add_audio_callback<F: FnMut(&AudioFrame) + Send + 'static>(
+    mix: usize,
+    conversion: Option<ResampleInfo>,
+    callback: F,
+) -> Option<RawAudioSubscription> {
+    let audio = unsafe { obs_get_audio() };
+    if audio.is_null() || mix >= MAX_AUDIO_MIXES as usize {
+        return None;
+    }
+    let channels = match conversion {
+        Some(conversion) => conversion.channels(),
+        None => unsafe { audio_output_get_channels(audio) },
+    };
+    if channels == 0 {
+        return None;
+    }
+
+    let param = Box::into_raw(Box::new(AudioParam {
+        mix,
+        channels,
+        callback: Box::new(callback),
+    }));
+    let raw = conversion.map(|conversion| audio_convert_info {
+        samples_per_sec: conversion.sample_rate,
+        format: audio_format_AUDIO_FORMAT_FLOAT_PLANAR,
+        speakers: conversion.speakers.as_raw(),
+        allow_clipping: true,
+    });
+    unsafe {
+        obs_add_raw_audio_callback(
+            mix,
+            raw.as_ref()
+                .map_or(std::ptr::null(), |raw| raw as *const audio_convert_info),
+            Some(raw_audio),
+            param as *mut c_void,
+        );
+    }
+    Some(RawAudioSubscription { param })
+}
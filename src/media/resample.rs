@@ -0,0 +1,273 @@
+//! Sample rate and channel layout conversion for planar float audio.
+//!
+//! [`Resampler`] uses libobs' resampler, which is backed by swresample and
+//! can also remix between speaker layouts. [`LinearResampler`] is a simple
+//! pure Rust implementation that doesn't need libobs to be running, e.g. for
+//! unit tests. Both implement [`Resample`].
+//!
+//! ```ignore
+//! // in `create`, convert from the output rate to 48 kHz
+//! let info = create.with_audio(|audio| audio.info());
+//! let resampler = Resampler::new(
+//!     ResampleInfo::from_audio(&info),
+//!     ResampleInfo::new(48000, SpeakerLayout::Mono),
+//! );
+//! ```
+
+use std::ptr;
+
+use obs_sys::{
+    audio_format_AUDIO_FORMAT_FLOAT_PLANAR, audio_resampler_create, audio_resampler_destroy,
+    audio_resampler_resample, audio_resampler_t, resample_info, MAX_AV_PLANES,
+};
+
+use super::{AudioInfo, SpeakerLayout};
+
+/// Rate and layout of planar float audio on one side of a resampler.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResampleInfo {
+    pub sample_rate: u32,
+    pub speakers: SpeakerLayout,
+}
+
+impl ResampleInfo {
+    pub fn new(sample_rate: u32, speakers: SpeakerLayout) -> Self {
+        Self {
+            sample_rate,
+            speakers,
+        }
+    }
+
+    /// The rate and layout of an audio output, e.g. from
+    /// [`AudioRef::info`](super::AudioRef::info).
+    pub fn from_audio(info: &AudioInfo) -> Self {
+        Self::new(info.sample_rate as u32, info.speakers)
+    }
+
+    pub fn channels(&self) -> usize {
+        self.speakers.channels()
+    }
+
+    fn as_raw(&self) -> resample_info {
+        resample_info {
+            samples_per_sec: self.sample_rate,
+            format: audio_format_AUDIO_FORMAT_FLOAT_PLANAR,
+            speakers: self.speakers.as_raw(),
+        }
+    }
+}
+
+/// Converts planar audio in blocks.
+pub trait Resample {
+    /// Convert one block of `input`, with one slice per input channel, and
+    /// append the result to `output`, with one buffer per output channel.
+    ///
+    /// Returns `false` if the channel counts don't match or conversion
+    /// failed.
+    fn resample(&mut self, input: &[&[f32]], output: &mut [Vec<f32>]) -> bool;
+}
+
+/// Resampler using libobs' `audio_resampler_t`.
+pub struct Resampler {
+    inner: *mut audio_resampler_t,
+    from: ResampleInfo,
+    to: ResampleInfo,
+    delay: u64,
+}
+
+impl Resampler {
+    /// Returns `None` if either layout is unknown or libobs failed to create
+    /// the resampler.
+    pub fn new(from: ResampleInfo, to: ResampleInfo) -> Option<Self> {
+        if from.channels() == 0 || to.channels() == 0 {
+            return None;
+        }
+        let inner = unsafe { audio_resampler_create(&to.as_raw(), &from.as_raw()) };
+        if inner.is_null() {
+            return None;
+        }
+        Some(Self {
+            inner,
+            from,
+            to,
+            delay: 0,
+        })
+    }
+
+    pub fn from(&self) -> ResampleInfo {
+        self.from
+    }
+
+    pub fn to(&self) -> ResampleInfo {
+        self.to
+    }
+
+    /// Delay introduced by the resampler in nanoseconds, as of the last call
+    /// to [`resample`](Resample::resample). Subtract it from the input
+    /// timestamp to get the timestamp of the output.
+    pub fn delay(&self) -> u64 {
+        self.delay
+    }
+}
+
+impl Resample for Resampler {
+    fn resample(&mut self, input: &[&[f32]], output: &mut [Vec<f32>]) -> bool {
+        if input.len() != self.from.channels() || output.len() != self.to.channels() {
+            return false;
+        }
+        let frames = input.iter().map(|c| c.len()).min().unwrap_or(0);
+
+        let mut planes_in = [ptr::null::<u8>(); MAX_AV_PLANES as usize];
+        for (plane, channel) in planes_in.iter_mut().zip(input) {
+            *plane = channel.as_ptr() as *const u8;
+        }
+        let mut planes_out = [ptr::null_mut::<u8>(); MAX_AV_PLANES as usize];
+        let mut out_frames = 0;
+
+        let ok = unsafe {
+            audio_resampler_resample(
+                self.inner,
+                planes_out.as_mut_ptr(),
+                &mut out_frames,
+                &mut self.delay,
+                planes_in.as_ptr(),
+                frames as u32,
+            )
+        };
+        if !ok {
+            return false;
+        }
+
+        // the output points into the resampler's own buffer, which is only
+        // valid until the next call
+        for (plane, channel) in planes_out.iter().zip(output.iter_mut()) {
+            if plane.is_null() {
+                return false;
+            }
+            let samples =
+                unsafe { std::slice::from_raw_parts(*plane as *const f32, out_frames as usize) };
+            channel.extend_from_slice(samples);
+        }
+        true
+    }
+}
+
+impl Drop for Resampler {
+    fn drop(&mut self) {
+        unsafe { audio_resampler_destroy(self.inner) }
+    }
+}
+
+/// Pure Rust resampler using linear interpolation. It changes the sample rate
+/// only, each output channel is the matching input channel.
+pub struct LinearResampler {
+    /// Input samples per output sample.
+    step: f64,
+    /// Position of the next output sample, relative to the start of the next
+    /// block. `-1` is the last sample of the previous block.
+    position: f64,
+    last: Vec<f32>,
+}
+
+impl LinearResampler {
+    pub fn new(from_rate: u32, to_rate: u32, channels: usize) -> Self {
+        Self {
+            step: from_rate as f64 / to_rate as f64,
+            position: 0.,
+            last: vec![0.; channels],
+        }
+    }
+
+    pub fn channels(&self) -> usize {
+        self.last.len()
+    }
+
+    /// Forget any previous input, e.g. after a seek.
+    pub fn reset(&mut self) {
+        self.position = 0.;
+        self.last.iter_mut().for_each(|s| *s = 0.);
+    }
+}
+
+impl Resample for LinearResampler {
+    fn resample(&mut self, input: &[&[f32]], output: &mut [Vec<f32>]) -> bool {
+        if input.len() != self.channels() || output.len() != self.channels() {
+            return false;
+        }
+        let frames = input.iter().map(|c| c.len()).min().unwrap_or(0);
+        if frames == 0 {
+            return true;
+        }
+
+        let mut end = self.position;
+        for ((channel, out), last) in input.iter().zip(output.iter_mut()).zip(&mut self.last) {
+            let sample = |i: isize| if i < 0 { *last } else { channel[i as usize] };
+            let mut position = self.position;
+            while position <= (frames - 1) as f64 {
+                let index = position.floor();
+                let fraction = (position - index) as f32;
+                let a = sample(index as isize);
+                // landing exactly on the last input sample of the block, there
+                // is nothing after it to interpolate towards
+                if fraction == 0. {
+                    out.push(a);
+                } else {
+                    let b = sample(index as isize + 1);
+                    out.push(a + (b - a) * fraction);
+                }
+                position += self.step;
+            }
+            *last = channel[frames - 1];
+            end = position;
+        }
+        self.position = end - frames as f64;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(resampler: &mut LinearResampler, input: &[f32], block: usize) -> Vec<f32> {
+        let mut output = vec![Vec::new()];
+        for chunk in input.chunks(block) {
+            assert!(resampler.resample(&[chunk], &mut output));
+        }
+        output.pop().unwrap()
+    }
+
+    #[test]
+    fn same_rate_is_passthrough() {
+        let input: Vec<f32> = (0..100).map(|i| i as f32).collect();
+        let output = run(&mut LinearResampler::new(48000, 48000, 1), &input, 7);
+        assert_eq!(output, input);
+    }
+
+    #[test]
+    fn doubling_interpolates() {
+        let input = [0., 2., 4., 6.];
+        let output = run(&mut LinearResampler::new(24000, 48000, 1), &input, 4);
+        assert_eq!(output, vec![0., 1., 2., 3., 4., 5., 6.]);
+    }
+
+    #[test]
+    fn blocks_match_single_call() {
+        let input: Vec<f32> = (0..480).map(|i| (i as f32 * 0.05).sin()).collect();
+        let whole = run(&mut LinearResampler::new(44100, 48000, 1), &input, 480);
+        let blocks = run(&mut LinearResampler::new(44100, 48000, 1), &input, 37);
+        assert_eq!(whole.len(), blocks.len());
+        for (a, b) in whole.iter().zip(&blocks) {
+            assert!((a - b).abs() < 1e-5);
+        }
+        // 480 samples at 44.1 kHz is about 522 at 48 kHz
+        assert!((whole.len() as i32 - 522).abs() <= 1);
+    }
+
+    #[test]
+    fn rejects_wrong_channel_count() {
+        let mut resampler = LinearResampler::new(44100, 48000, 2);
+        let mut output = vec![Vec::new()];
+        assert!(!resampler.resample(&[&[0.; 4]], &mut output));
+    }
+}
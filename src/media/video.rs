@@ -1,5 +1,8 @@
 use obs_sys::{
-    obs_source_frame, video_data, video_format, video_format_VIDEO_FORMAT_AYUV,
+    obs_source_frame, video_colorspace, video_colorspace_VIDEO_CS_2100_HLG,
+    video_colorspace_VIDEO_CS_2100_PQ, video_colorspace_VIDEO_CS_601,
+    video_colorspace_VIDEO_CS_709, video_colorspace_VIDEO_CS_DEFAULT,
+    video_colorspace_VIDEO_CS_SRGB, video_data, video_format, video_format_VIDEO_FORMAT_AYUV,
     video_format_VIDEO_FORMAT_BGR3, video_format_VIDEO_FORMAT_BGRA, video_format_VIDEO_FORMAT_BGRX,
     video_format_VIDEO_FORMAT_I010, video_format_VIDEO_FORMAT_I210, video_format_VIDEO_FORMAT_I40A,
     video_format_VIDEO_FORMAT_I412, video_format_VIDEO_FORMAT_I420, video_format_VIDEO_FORMAT_I422,
@@ -8,10 +11,17 @@ use obs_sys::{
     video_format_VIDEO_FORMAT_UYVY, video_format_VIDEO_FORMAT_Y800, video_format_VIDEO_FORMAT_YA2L,
     video_format_VIDEO_FORMAT_YUVA, video_format_VIDEO_FORMAT_YUY2, video_format_VIDEO_FORMAT_YVYU,
     video_output_get_format, video_output_get_frame_rate, video_output_get_height,
-    video_output_get_width, video_t,
+    video_output_get_width, video_range_type, video_range_type_VIDEO_RANGE_DEFAULT,
+    video_range_type_VIDEO_RANGE_FULL, video_range_type_VIDEO_RANGE_PARTIAL, video_t, video_trc,
+    video_trc_VIDEO_TRC_DEFAULT, video_trc_VIDEO_TRC_HLG, video_trc_VIDEO_TRC_PQ,
+    video_trc_VIDEO_TRC_SRGB,
 };
 
-use crate::native_enum;
+use super::{convert::ColorRange, VideoFrame};
+use crate::{
+    graphics::{Matrix4, Vec4},
+    native_enum,
+};
 
 native_enum!(VideoFormat, video_format {
     None => VIDEO_FORMAT_NONE,
@@ -63,6 +73,39 @@ native_enum!(VideoFormat, video_format {
     YA2L => VIDEO_FORMAT_YA2L,
 });
 
+native_enum!(
+    /// Transfer characteristics of a frame.
+    VideoTrc, video_trc {
+    Default => VIDEO_TRC_DEFAULT,
+    Srgb => VIDEO_TRC_SRGB,
+    /// Perceptual quantizer, used for HDR10.
+    Pq => VIDEO_TRC_PQ,
+    /// Hybrid log-gamma.
+    Hlg => VIDEO_TRC_HLG,
+});
+
+native_enum!(
+    /// Color space of video converted by libobs.
+    VideoColorSpace, video_colorspace {
+    /// The color space of the video output.
+    Default => VIDEO_CS_DEFAULT,
+    Bt601 => VIDEO_CS_601,
+    Bt709 => VIDEO_CS_709,
+    Srgb => VIDEO_CS_SRGB,
+    Bt2100Pq => VIDEO_CS_2100_PQ,
+    Bt2100Hlg => VIDEO_CS_2100_HLG,
+});
+
+native_enum!(
+    /// Range of video converted by libobs.
+    VideoRange, video_range_type {
+    /// The range of the video output.
+    Default => VIDEO_RANGE_DEFAULT,
+    /// Limited range.
+    Partial => VIDEO_RANGE_PARTIAL,
+    Full => VIDEO_RANGE_FULL,
+});
+
 pub struct VideoDataSourceContext {
     pointer: *mut obs_source_frame,
 }
@@ -97,6 +140,79 @@ impl VideoDataSourceContext {
     pub fn timestamp(&self) -> u64 {
         unsafe { (*self.pointer).timestamp }
     }
+
+    /// Matrix converting normalized `(y, u, v, 1)` to `(r, g, b, 1)` with
+    /// [`Matrix4::transform`].
+    ///
+    /// libobs stores the coefficients of each output channel together, for
+    /// `dot` in a shader, so this is the transpose of the raw `color_matrix`.
+    pub fn color_matrix(&self) -> Matrix4 {
+        color_matrix_from_raw(unsafe { &(*self.pointer).color_matrix })
+    }
+
+    /// Lowest valid value of each YUV channel, normalized to 0..1.
+    pub fn color_range_min(&self) -> [f32; 3] {
+        unsafe { (*self.pointer).color_range_min }
+    }
+
+    /// Highest valid value of each YUV channel, normalized to 0..1.
+    pub fn color_range_max(&self) -> [f32; 3] {
+        unsafe { (*self.pointer).color_range_max }
+    }
+
+    pub fn full_range(&self) -> bool {
+        unsafe { (*self.pointer).full_range }
+    }
+
+    /// The range for converting the frame with
+    /// [`Converter`](super::convert::Converter).
+    pub fn range(&self) -> ColorRange {
+        if self.full_range() {
+            ColorRange::Full
+        } else {
+            ColorRange::Limited
+        }
+    }
+
+    /// Whether the frame is stored upside down.
+    pub fn flip(&self) -> bool {
+        unsafe { (*self.pointer).flip }
+    }
+
+    pub fn trc(&self) -> Option<VideoTrc> {
+        let raw = unsafe { (*self.pointer).trc };
+
+        VideoTrc::from_raw(raw as video_trc).ok()
+    }
+
+    /// Peak brightness of HDR frames in nits, or 0 if unknown.
+    pub fn max_luminance(&self) -> u16 {
+        unsafe { (*self.pointer).max_luminance }
+    }
+
+    /// The planes of the frame, or `None` if the format is unknown.
+    pub fn frame(&mut self) -> Option<VideoFrame<'_>> {
+        let format = self.format()?;
+        unsafe {
+            let frame = self.pointer.as_ref()?;
+            VideoFrame::from_raw(
+                format,
+                frame.width,
+                frame.height,
+                &frame.data,
+                &frame.linesize,
+            )
+        }
+    }
+}
+
+fn color_matrix_from_raw(m: &[f32; 16]) -> Matrix4 {
+    Matrix4 {
+        x: Vec4::new(m[0], m[4], m[8], m[12]),
+        y: Vec4::new(m[1], m[5], m[9], m[13]),
+        z: Vec4::new(m[2], m[6], m[10], m[14]),
+        t: Vec4::new(m[3], m[7], m[11], m[15]),
+    }
 }
 
 pub struct VideoDataOutputContext {
@@ -119,6 +235,27 @@ impl VideoDataOutputContext {
     pub fn timestamp(&self) -> u64 {
         unsafe { (*self.pointer).timestamp }
     }
+
+    /// The planes of the frame, using the size and format of the output's
+    /// video, see [`OutputContext::video`](crate::output::OutputContext::video).
+    ///
+    /// # Safety
+    ///
+    /// `info` must describe the frames actually delivered to the output: its
+    /// video info, or the conversion set with `obs_output_set_video_conversion`
+    /// if there is one. The planes are sized from `info`, so anything larger
+    /// reads out of bounds.
+    pub unsafe fn frame(&mut self, info: &VideoInfo) -> Option<VideoFrame<'_>> {
+        let format = info.format?;
+        let frame = self.pointer.as_ref()?;
+        VideoFrame::from_raw(
+            format,
+            info.width,
+            info.height,
+            &frame.data,
+            &frame.linesize,
+        )
+    }
 }
 
 #[allow(unused)]
@@ -139,6 +276,25 @@ pub enum FrameSize {
     FourPlane(usize, usize, usize, usize),
 }
 
+impl FrameSize {
+    /// Size of each plane in bytes, empty if unknown.
+    pub fn plane_sizes(&self) -> Vec<usize> {
+        match *self {
+            FrameSize::Unknown => vec![],
+            FrameSize::Planes { size, count } => vec![size; count],
+            FrameSize::OnePlane(a) => vec![a],
+            FrameSize::TwoPlane(a, b) => vec![a, b],
+            FrameSize::ThreePlane(a, b, c) => vec![a, b, c],
+            FrameSize::FourPlane(a, b, c, d) => vec![a, b, c, d],
+        }
+    }
+
+    /// Size of all planes together in bytes.
+    pub fn total(&self) -> usize {
+        self.plane_sizes().iter().sum()
+    }
+}
+
 impl VideoInfo {
     /// see https://github.com/obsproject/obs-studio/blob/a1e8075fba09f3b56ed43ead64cc3e340dd7a059/libobs/media-io/video-frame.c#L23
     pub fn frame_size(&self) -> FrameSize {
@@ -156,7 +312,7 @@ impl VideoInfo {
         match format {
             VideoFormat::None => FrameSize::Planes { size: 0, count: 0 },
             I420 => FrameSize::ThreePlane(full_size, quarter_size, quarter_size),
-            NV12 => FrameSize::TwoPlane(full_size, half_size * 2),
+            NV12 => FrameSize::TwoPlane(full_size, quarter_size * 2),
             Y800 => FrameSize::OnePlane(full_size),
             YVYU | UYVY | YUY2 => FrameSize::OnePlane(half_size * 4),
             BGRX | BGRA | RGBA | AYUV => FrameSize::OnePlane(full_size * 4),
@@ -225,3 +381,90 @@ impl VideoRef {
         VideoFormat::from_raw(raw).ok()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::media::convert::{ColorMatrix, Converter};
+
+    /// BT.709 limited range, as returned by `video_format_get_parameters`.
+    const BT709_LIMITED: [f32; 16] = [
+        1.164_384, 0.0, 1.792_741, -0.972_945, //
+        1.164_384, -0.213_249, -0.532_909, 0.301_483, //
+        1.164_384, 2.112_402, 0.0, -1.133_402, //
+        0.0, 0.0, 0.0, 1.0,
+    ];
+
+    fn plane_sizes(format: VideoFormat, width: u32, height: u32) -> Vec<usize> {
+        VideoInfo {
+            width,
+            height,
+            frame_rate: 30.,
+            format: Some(format),
+        }
+        .frame_size()
+        .plane_sizes()
+    }
+
+    #[test]
+    fn frame_size_odd_dimensions() {
+        // chroma of odd sizes rounds up, 5x3 has 3x2 chroma samples
+        assert_eq!(plane_sizes(VideoFormat::I420, 5, 3), [15, 6, 6]);
+        assert_eq!(plane_sizes(VideoFormat::NV12, 5, 3), [15, 12]);
+        assert_eq!(plane_sizes(VideoFormat::P010, 5, 3), [30, 24]);
+    }
+
+    #[test]
+    fn frame_size_matches_plane_layout() {
+        use VideoFormat::*;
+        for format in [
+            I420, NV12, YVYU, YUY2, UYVY, RGBA, BGRA, BGRX, Y800, I444, BGR3, I422, I40A, I42A,
+            YUVA, AYUV, I010, P010, I210, I412, YA2L,
+        ] {
+            let layout_sizes: Vec<usize> = format
+                .plane_layout(5, 3)
+                .iter()
+                .map(|plane| plane.row_bytes * plane.height as usize)
+                .collect();
+            assert_eq!(layout_sizes, plane_sizes(format, 5, 3), "{:?}", format);
+        }
+    }
+
+    #[test]
+    fn color_matrix_matches_converter() {
+        let matrix = color_matrix_from_raw(&BT709_LIMITED);
+        let converter = Converter::new(ColorMatrix::Bt709, ColorRange::Limited);
+        let info = VideoInfo {
+            width: 1,
+            height: 1,
+            frame_rate: 30.,
+            format: Some(VideoFormat::I444),
+        };
+
+        for yuv in [
+            [16, 128, 128],
+            [235, 128, 128],
+            [63, 102, 240],
+            [173, 42, 26],
+        ] {
+            let mut buffer = yuv;
+            let frame = VideoFrame::from_buffer(&info, &mut buffer).unwrap();
+            let mut expected = [0; 4];
+            assert!(converter.to_rgba8(&frame, &mut expected));
+
+            let [y, u, v] = yuv.map(|c| c as f32 / 255.);
+            let rgb = matrix.transform(&Vec4::new(y, u, v, 1.));
+            assert!((rgb.w() - 1.).abs() < 1e-6);
+            for (channel, expected) in [rgb.x(), rgb.y(), rgb.z()].iter().zip(expected) {
+                let channel = (channel.clamp(0., 1.) * 255.).round();
+                assert!(
+                    (channel - expected as f32).abs() <= 1.,
+                    "{:?}: {:?} != {:?}",
+                    yuv,
+                    rgb,
+                    expected
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,465 @@
+//! Audio levels of sources, for meters and volume sliders.
+//!
+//! A [`VolMeter`] reports the levels of the source it is attached to after
+//! every block of audio, in dBFS. A [`Fader`] controls the volume of a source,
+//! mapping between dB and the position of a slider, its deflection, which
+//! goes from `0.0` to `1.0`.
+//!
+//! ```ignore
+//! let mut meter = VolMeter::new(FaderType::Iec).unwrap();
+//! meter.attach(&mic);
+//! meter.add_callback(move |levels| {
+//!     let peak = levels.peak().iter().copied().fold(f32::NEG_INFINITY, f32::max);
+//!     sender.send(FaderType::Iec.db_to_deflection(peak)).ok();
+//! });
+//! ```
+
+use std::os::raw::c_void;
+
+use obs_sys::{
+    obs_db_to_mul, obs_fader_add_callback, obs_fader_attach_source, obs_fader_create,
+    obs_fader_destroy, obs_fader_detach_source, obs_fader_get_db, obs_fader_get_deflection,
+    obs_fader_get_mul, obs_fader_remove_callback, obs_fader_set_db, obs_fader_set_deflection,
+    obs_fader_set_mul, obs_fader_t, obs_fader_type, obs_fader_type_OBS_FADER_CUBIC,
+    obs_fader_type_OBS_FADER_IEC, obs_fader_type_OBS_FADER_LOG, obs_mul_to_db, obs_peak_meter_type,
+    obs_peak_meter_type_SAMPLE_PEAK_METER, obs_peak_meter_type_TRUE_PEAK_METER,
+    obs_volmeter_add_callback, obs_volmeter_attach_source, obs_volmeter_create,
+    obs_volmeter_destroy, obs_volmeter_detach_source, obs_volmeter_get_nr_channels,
+    obs_volmeter_get_update_interval, obs_volmeter_remove_callback,
+    obs_volmeter_set_peak_meter_type, obs_volmeter_set_update_interval, obs_volmeter_t,
+    MAX_AUDIO_CHANNELS,
+};
+
+use crate::{native_enum, source::SourceRef, wrapper::PtrWrapper};
+
+native_enum!(
+    /// How dB values are mapped to a deflection between `0.0` and `1.0`.
+    FaderType, obs_fader_type {
+    /// Deflection is the cube root of the multiplier
+    Cubic => OBS_FADER_CUBIC,
+    /// Linear segments as defined in IEC 60-268-18, used by the OBS mixer
+    Iec => OBS_FADER_IEC,
+    /// Logarithmic between -96 dB and 0 dB
+    Log => OBS_FADER_LOG,
+});
+
+native_enum!(PeakMeterType, obs_peak_meter_type {
+    /// Maximum of all samples
+    SamplePeak => SAMPLE_PEAK_METER,
+    /// Maximum between samples, using 4x oversampling
+    TruePeak => TRUE_PEAK_METER,
+});
+
+/// Convert a volume multiplier to dB. `0.0` is `-inf` dB.
+pub fn mul_to_db(mul: f32) -> f32 {
+    unsafe { obs_mul_to_db(mul) }
+}
+
+/// Convert dB to a volume multiplier. `-inf` dB is `0.0`.
+pub fn db_to_mul(db: f32) -> f32 {
+    unsafe { obs_db_to_mul(db) }
+}
+
+// the same mappings as libobs/obs-audio-controls.c, which doesn't export them
+
+const LOG_OFFSET_DB: f32 = 6.;
+const LOG_RANGE_DB: f32 = 96.;
+/// `-log10(LOG_OFFSET_DB)`
+const LOG_OFFSET_VAL: f32 = -0.778_151_2;
+/// `-log10(LOG_RANGE_DB + LOG_OFFSET_DB)`
+const LOG_RANGE_VAL: f32 = -2.008_600_2;
+
+impl FaderType {
+    /// The deflection between `0.0` and `1.0` at which a fader of this type
+    /// shows `db`.
+    pub fn db_to_deflection(&self, db: f32) -> f32 {
+        match self {
+            FaderType::Cubic => {
+                if db >= 0. {
+                    1.
+                } else if db == f32::NEG_INFINITY {
+                    0.
+                } else {
+                    10f32.powf(db / 20.).cbrt()
+                }
+            }
+            FaderType::Iec => {
+                if db >= 0. {
+                    1.
+                } else if db >= -9. {
+                    (db + 9.) / 9. * 0.25 + 0.75
+                } else if db >= -20. {
+                    (db + 20.) / 11. * 0.15 + 0.6
+                } else if db >= -30. {
+                    (db + 30.) / 10. * 0.15 + 0.45
+                } else if db >= -40. {
+                    (db + 40.) / 10. * 0.15 + 0.3
+                } else if db >= -50. {
+                    (db + 50.) / 10. * 0.15 + 0.15
+                } else if db >= -60. {
+                    (db + 60.) / 10. * 0.075 + 0.075
+                } else if db >= -114. {
+                    (db + 150.) / 90. * 0.075
+                } else {
+                    0.
+                }
+            }
+            FaderType::Log => {
+                if db >= 0. {
+                    1.
+                } else if db <= -LOG_RANGE_DB {
+                    0.
+                } else {
+                    (-(-db + LOG_OFFSET_DB).log10() - LOG_RANGE_VAL)
+                        / (LOG_OFFSET_VAL - LOG_RANGE_VAL)
+                }
+            }
+        }
+    }
+
+    /// The dB value shown by a fader of this type at `deflection`.
+    pub fn deflection_to_db(&self, deflection: f32) -> f32 {
+        if deflection >= 1. {
+            return 0.;
+        }
+        if deflection <= 0. {
+            return f32::NEG_INFINITY;
+        }
+        match self {
+            FaderType::Cubic => 20. * deflection.powi(3).log10(),
+            FaderType::Iec => {
+                if deflection >= 0.75 {
+                    (deflection - 0.75) / 0.25 * 9. - 9.
+                } else if deflection >= 0.6 {
+                    (deflection - 0.6) / 0.15 * 11. - 20.
+                } else if deflection >= 0.45 {
+                    (deflection - 0.45) / 0.15 * 10. - 30.
+                } else if deflection >= 0.3 {
+                    (deflection - 0.3) / 0.15 * 10. - 40.
+                } else if deflection >= 0.15 {
+                    (deflection - 0.15) / 0.15 * 10. - 50.
+                } else if deflection >= 0.075 {
+                    (deflection - 0.075) / 0.075 * 10. - 60.
+                } else {
+                    deflection / 0.075 * 90. - 150.
+                }
+            }
+            FaderType::Log => {
+                -(LOG_RANGE_DB + LOG_OFFSET_DB)
+                    * ((LOG_RANGE_DB + LOG_OFFSET_DB) / LOG_OFFSET_DB).powf(-deflection)
+                    + LOG_OFFSET_DB
+            }
+        }
+    }
+}
+
+/// Levels of each channel in dBFS, passed to [`VolMeter::add_callback`].
+///
+/// These already include the volume of the source, except for
+/// [`input_peak`](Self::input_peak).
+pub struct VolumeLevels<'a> {
+    magnitude: &'a [f32],
+    peak: &'a [f32],
+    input_peak: &'a [f32],
+}
+
+impl VolumeLevels<'_> {
+    pub fn channels(&self) -> usize {
+        self.magnitude.len()
+    }
+
+    /// RMS level over the update interval.
+    pub fn magnitude(&self) -> &[f32] {
+        self.magnitude
+    }
+
+    /// Peak level over the update interval.
+    pub fn peak(&self) -> &[f32] {
+        self.peak
+    }
+
+    /// Peak level before the volume of the source is applied.
+    pub fn input_peak(&self) -> &[f32] {
+        self.input_peak
+    }
+}
+
+type LevelsCallback = Box<dyn FnMut(&VolumeLevels) + Send>;
+
+struct LevelsParam {
+    volmeter: *mut obs_volmeter_t,
+    callback: LevelsCallback,
+}
+
+unsafe extern "C" fn levels_updated(
+    param: *mut c_void,
+    magnitude: *const f32,
+    peak: *const f32,
+    input_peak: *const f32,
+) {
+    let param = &mut *(param as *mut LevelsParam);
+    let channels = (obs_volmeter_get_nr_channels(param.volmeter).max(0) as usize)
+        .min(MAX_AUDIO_CHANNELS as usize);
+    let levels = VolumeLevels {
+        magnitude: std::slice::from_raw_parts(magnitude, channels),
+        peak: std::slice::from_raw_parts(peak, channels),
+        input_peak: std::slice::from_raw_parts(input_peak, channels),
+    };
+    (param.callback)(&levels);
+}
+
+/// Measures the levels of a source.
+pub struct VolMeter {
+    inner: *mut obs_volmeter_t,
+    // boxed so the pointers passed to libobs stay valid when the Vec grows
+    #[allow(clippy::vec_box)]
+    callbacks: Vec<Box<LevelsParam>>,
+}
+
+impl VolMeter {
+    /// `fader` only affects how the volume of the source is mapped, the
+    /// levels are always reported in dB. Returns `None` if libobs failed to
+    /// create the meter.
+    pub fn new(fader: FaderType) -> Option<Self> {
+        let inner = unsafe { obs_volmeter_create(fader.as_raw()) };
+        if inner.is_null() {
+            return None;
+        }
+        Some(Self {
+            inner,
+            callbacks: Vec::new(),
+        })
+    }
+
+    /// Start measuring `source`, replacing any previously attached source.
+    /// Returns `false` if the source has no audio.
+    pub fn attach(&mut self, source: &SourceRef) -> bool {
+        unsafe { obs_volmeter_attach_source(self.inner, source.as_ptr_mut()) }
+    }
+
+    pub fn detach(&mut self) {
+        unsafe { obs_volmeter_detach_source(self.inner) }
+    }
+
+    pub fn set_peak_meter_type(&mut self, peak_meter: PeakMeterType) {
+        unsafe { obs_volmeter_set_peak_meter_type(self.inner, peak_meter.as_raw()) }
+    }
+
+    /// How often levels are reported, in milliseconds. Defaults to 50.
+    pub fn update_interval(&self) -> u32 {
+        unsafe { obs_volmeter_get_update_interval(self.inner) as u32 }
+    }
+
+    pub fn set_update_interval(&mut self, ms: u32) {
+        unsafe { obs_volmeter_set_update_interval(self.inner, ms as _) }
+    }
+
+    /// Channels of the attached source.
+    pub fn channels(&self) -> usize {
+        unsafe { obs_volmeter_get_nr_channels(self.inner).max(0) as usize }
+    }
+
+    /// Call `callback` with the levels after every update interval. It is
+    /// called from the audio thread, and removed when the meter is dropped.
+    pub fn add_callback<F: FnMut(&VolumeLevels) + Send + 'static>(&mut self, callback: F) {
+        let mut param = Box::new(LevelsParam {
+            volmeter: self.inner,
+            callback: Box::new(callback),
+        });
+        unsafe {
+            obs_volmeter_add_callback(
+                self.inner,
+                Some(levels_updated),
+                &mut *param as *mut LevelsParam as *mut c_void,
+            );
+        }
+        self.callbacks.push(param);
+    }
+}
+
+impl Drop for VolMeter {
+    fn drop(&mut self) {
+        unsafe {
+            // libobs holds its callback lock while calling back, so once
+            // removed the closures can't be running anymore
+            for param in &mut self.callbacks {
+                obs_volmeter_remove_callback(
+                    self.inner,
+                    Some(levels_updated),
+                    &mut **param as *mut LevelsParam as *mut c_void,
+                );
+            }
+            obs_volmeter_destroy(self.inner);
+        }
+    }
+}
+
+type FaderCallback = Box<dyn FnMut(f32) + Send>;
+
+unsafe extern "C" fn fader_changed(param: *mut c_void, db: f32) {
+    let callback = &mut *(param as *mut FaderCallback);
+    callback(db);
+}
+
+/// Controls the volume of a source, which stays in sync with the volume
+/// set anywhere else, e.g. in the OBS mixer.
+pub struct Fader {
+    inner: *mut obs_fader_t,
+    fader: FaderType,
+    #[allow(clippy::vec_box)]
+    callbacks: Vec<Box<FaderCallback>>,
+}
+
+impl Fader {
+    /// Returns `None` if libobs failed to create the fader.
+    pub fn new(fader: FaderType) -> Option<Self> {
+        let inner = unsafe { obs_fader_create(fader.as_raw()) };
+        if inner.is_null() {
+            return None;
+        }
+        Some(Self {
+            inner,
+            fader,
+            callbacks: Vec::new(),
+        })
+    }
+
+    pub fn fader_type(&self) -> FaderType {
+        self.fader
+    }
+
+    /// Control the volume of `source`, replacing any previously attached
+    /// source. Returns `false` if the source has no audio.
+    pub fn attach(&mut self, source: &SourceRef) -> bool {
+        unsafe { obs_fader_attach_source(self.inner, source.as_ptr_mut()) }
+    }
+
+    pub fn detach(&mut self) {
+        unsafe { obs_fader_detach_source(self.inner) }
+    }
+
+    pub fn db(&self) -> f32 {
+        unsafe { obs_fader_get_db(self.inner) }
+    }
+
+    /// Returns `false` if `db` was out of range and got clamped.
+    pub fn set_db(&mut self, db: f32) -> bool {
+        unsafe { obs_fader_set_db(self.inner, db) }
+    }
+
+    pub fn deflection(&self) -> f32 {
+        unsafe { obs_fader_get_deflection(self.inner) }
+    }
+
+    /// Returns `false` if `deflection` was out of range and got clamped.
+    pub fn set_deflection(&mut self, deflection: f32) -> bool {
+        unsafe { obs_fader_set_deflection(self.inner, deflection) }
+    }
+
+    /// The volume as a multiplier.
+    pub fn mul(&self) -> f32 {
+        unsafe { obs_fader_get_mul(self.inner) }
+    }
+
+    /// Returns `false` if `mul` was out of range and got clamped.
+    pub fn set_mul(&mut self, mul: f32) -> bool {
+        unsafe { obs_fader_set_mul(self.inner, mul) }
+    }
+
+    /// Call `callback` with the new dB value whenever the volume changes.
+    /// It is removed when the fader is dropped.
+    pub fn add_callback<F: FnMut(f32) + Send + 'static>(&mut self, callback: F) {
+        let mut callback: Box<FaderCallback> = Box::new(Box::new(callback));
+        unsafe {
+            obs_fader_add_callback(
+                self.inner,
+                Some(fader_changed),
+                &mut *callback as *mut FaderCallback as *mut c_void,
+            );
+        }
+        self.callbacks.push(callback);
+    }
+}
+
+impl Drop for Fader {
+    fn drop(&mut self) {
+        unsafe {
+            for callback in &mut self.callbacks {
+                obs_fader_remove_callback(
+                    self.inner,
+                    Some(fader_changed),
+                    &mut **callback as *mut FaderCallback as *mut c_void,
+                );
+            }
+            obs_fader_destroy(self.inner);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_near(a: f32, b: f32) {
+        assert!((a - b).abs() < 1e-5, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn iec_breakpoints() {
+        let iec = FaderType::Iec;
+        for (db, deflection) in [
+            (0., 1.),
+            (-9., 0.75),
+            (-20., 0.6),
+            (-30., 0.45),
+            (-40., 0.3),
+            (-50., 0.15),
+            (-60., 0.075),
+            (-114., 0.03),
+        ] {
+            assert_near(iec.db_to_deflection(db), deflection);
+            assert_near(iec.deflection_to_db(deflection), db);
+        }
+        // below -114 dB the fader bottoms out
+        assert_eq!(iec.db_to_deflection(-120.), 0.);
+    }
+
+    #[test]
+    fn limits() {
+        for fader in [FaderType::Cubic, FaderType::Iec, FaderType::Log] {
+            assert_eq!(fader.db_to_deflection(0.), 1.);
+            assert_eq!(fader.db_to_deflection(f32::NEG_INFINITY), 0.);
+            assert_eq!(fader.deflection_to_db(1.), 0.);
+            assert_eq!(fader.deflection_to_db(0.), f32::NEG_INFINITY);
+        }
+        assert_eq!(FaderType::Log.db_to_deflection(-96.), 0.);
+    }
+
+    #[test]
+    fn cubic_round_trip() {
+        let cubic = FaderType::Cubic;
+        // half the amplitude is a deflection of its cube root
+        assert_near(cubic.db_to_deflection(20. * 0.5f32.log10()), 0.5f32.cbrt());
+        for deflection in [0.01, 0.1, 0.25, 0.5, 0.75, 0.99] {
+            assert_near(
+                cubic.db_to_deflection(cubic.deflection_to_db(deflection)),
+                deflection,
+            );
+        }
+        for db in [-90., -40., -12., -6., -0.5] {
+            let back = cubic.deflection_to_db(cubic.db_to_deflection(db));
+            assert!((back - db).abs() < 1e-3, "{} != {}", back, db);
+        }
+    }
+
+    #[test]
+    fn log_round_trip() {
+        let log = FaderType::Log;
+        for deflection in [0.05, 0.25, 0.5, 0.75, 0.95] {
+            assert_near(
+                log.db_to_deflection(log.deflection_to_db(deflection)),
+                deflection,
+            );
+        }
+    }
+}
@@ -27,7 +27,7 @@ macro_rules! native_enum {
     ($(#[$($attrs_enum:tt)*])* $name:ident,$native_name:ident { $($(#[$($attrss:tt)*])* $rust:ident => $native:ident,)* }) => {
         paste::item! {
             $(#[$($attrs_enum)*])*
-            #[derive(Debug, Clone, Copy, Eq, PartialEq)]
+            #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
             pub enum $name {
                 $(
                     $(#[$($attrss)*])*
@@ -20,6 +20,16 @@ pub enum Error {
     /// Error converting path to str
     #[error("Path Error: utf8")]
     PathUtf8,
+    /// Effect failed to compile
+    #[error("Shader Compile Error: {name}: {message}")]
+    ShaderCompile {
+        name: String,
+        message: crate::graphics::ShaderCompileOutput,
+    },
+    /// Font could not be found or loaded
+    #[cfg(feature = "text")]
+    #[error("Font Error: {0}")]
+    Font(String),
 }
 
 pub trait OptionExt {
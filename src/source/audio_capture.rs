@@ -0,0 +1,80 @@
+use std::os::raw::c_void;
+
+use obs_sys::{
+    audio_data, obs_audio_data, obs_source_add_audio_capture_callback,
+    obs_source_remove_audio_capture_callback, obs_source_t,
+};
+
+use super::SourceRef;
+use crate::media::audio::AudioFrame;
+
+type CaptureCallback = Box<dyn FnMut(&AudioFrame, bool) + Send>;
+
+unsafe extern "C" fn audio_captured(
+    param: *mut c_void,
+    _source: *mut obs_source_t,
+    audio: *const audio_data,
+    muted: bool,
+) {
+    let callback = &mut *(param as *mut CaptureCallback);
+    // `audio_data` has the same layout as `obs_audio_data`, and the frame is
+    // only handed out by shared reference so it's never written to
+    let frame = AudioFrame::from_raw(audio as *mut obs_audio_data);
+    callback(&frame, muted);
+}
+
+/// Removes the callback added by [`SourceRef::add_audio_capture_callback`]
+/// when dropped.
+#[must_use = "the callback is removed when the guard is dropped"]
+pub struct AudioCaptureGuard {
+    source: SourceRef,
+    callback: *mut CaptureCallback,
+}
+
+impl AudioCaptureGuard {
+    pub fn source(&self) -> &SourceRef {
+        &self.source
+    }
+}
+
+impl Drop for AudioCaptureGuard {
+    fn drop(&mut self) {
+        unsafe {
+            // libobs holds its callback lock while calling back, so once
+            // removed the closure can't be running anymore
+            obs_source_remove_audio_capture_callback(
+                self.source.inner,
+                Some(audio_captured),
+                self.callback as *mut c_void,
+            );
+            drop(Box::from_raw(self.callback));
+        }
+    }
+}
+
+impl SourceRef {
+    /// Call `callback` with the audio of this source after its filters, but
+    /// before its volume is applied, along with whether the source is muted.
+    ///
+    /// It is called from the audio thread, in the format of the audio output,
+    /// until the returned guard is dropped. The guard keeps a reference to
+    /// the source.
+    pub fn add_audio_capture_callback<F: FnMut(&AudioFrame, bool) + Send + 'static>(
+        &self,
+        callback: F,
+    ) -> AudioCaptureGuard {
+        let callback: Box<CaptureCallback> = Box::new(Box::new(callback));
+        let callback = Box::into_raw(callback);
+        unsafe {
+            obs_source_add_audio_capture_callback(
+                self.inner,
+                Some(audio_captured),
+                callback as *mut c_void,
+            );
+        }
+        AudioCaptureGuard {
+            source: self.clone(),
+            callback,
+        }
+    }
+}
@@ -0,0 +1,381 @@
+use std::marker::PhantomData;
+
+use obs_sys::{
+    audio_output_get_channels, obs_get_audio, obs_source_audio_active, obs_source_audio_mix,
+    obs_source_audio_pending, obs_source_get_audio_mix, obs_source_get_audio_mixers,
+    obs_source_get_audio_timestamp, AUDIO_OUTPUT_FRAMES, MAX_AUDIO_MIXES, MAX_AV_PLANES,
+};
+
+use super::SourceRef;
+
+fn frames_between(sample_rate: usize, start: u64, end: u64) -> usize {
+    ((end - start) as u128 * sample_rate as u128 / 1_000_000_000) as usize
+}
+
+/// The audio a source rendered for each mix, from [`SourceRef::audio_mix`].
+///
+/// Only valid during [`AudioRenderSource::audio_render`](super::traits::AudioRenderSource::audio_render)
+/// of the parent source.
+pub struct AudioMix<'a> {
+    raw: obs_source_audio_mix,
+    timestamp: u64,
+    channels: usize,
+    _marker: PhantomData<&'a SourceRef>,
+}
+
+impl AudioMix<'_> {
+    /// Timestamp of the first sample in nanoseconds.
+    pub fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+
+    pub fn channels(&self) -> usize {
+        self.channels
+    }
+
+    /// The samples of `channel` in `mix`, [`AUDIO_OUTPUT_FRAMES`] long.
+    pub fn channel(&self, mix: usize, channel: usize) -> Option<&[f32]> {
+        if channel >= self.channels {
+            return None;
+        }
+        let data = self.raw.output.get(mix)?.data[channel];
+        if data.is_null() {
+            return None;
+        }
+        unsafe {
+            Some(std::slice::from_raw_parts(
+                data,
+                AUDIO_OUTPUT_FRAMES as usize,
+            ))
+        }
+    }
+}
+
+/// Where a source writes its audio in
+/// [`AudioRenderSource::audio_render`](super::traits::AudioRenderSource::audio_render).
+///
+/// There is a buffer of [`AUDIO_OUTPUT_FRAMES`] samples for each channel of
+/// each of the 6 mixes, which starts out silent.
+pub struct AudioMixOutput<'a> {
+    raw: &'a mut obs_source_audio_mix,
+    timestamp: &'a mut u64,
+    mixers: u32,
+    channels: usize,
+    sample_rate: usize,
+}
+
+impl<'a> AudioMixOutput<'a> {
+    pub(crate) fn new(
+        raw: &'a mut obs_source_audio_mix,
+        timestamp: &'a mut u64,
+        mixers: u32,
+        channels: usize,
+        sample_rate: usize,
+    ) -> Self {
+        Self {
+            raw,
+            timestamp,
+            mixers,
+            channels: channels.min(MAX_AV_PLANES as usize),
+            sample_rate,
+        }
+    }
+
+    /// Bit mask of the mixes that are being rendered.
+    pub fn mixers(&self) -> u32 {
+        self.mixers
+    }
+
+    pub fn is_mix_enabled(&self, mix: usize) -> bool {
+        mix < MAX_AUDIO_MIXES as usize && self.mixers & (1 << mix) != 0
+    }
+
+    pub fn channels(&self) -> usize {
+        self.channels
+    }
+
+    pub fn sample_rate(&self) -> usize {
+        self.sample_rate
+    }
+
+    /// Samples in each channel buffer.
+    pub fn frames(&self) -> usize {
+        AUDIO_OUTPUT_FRAMES as usize
+    }
+
+    /// Timestamp of the first sample in nanoseconds.
+    pub fn timestamp(&self) -> u64 {
+        *self.timestamp
+    }
+
+    pub fn set_timestamp(&mut self, timestamp: u64) {
+        *self.timestamp = timestamp;
+    }
+
+    /// The buffer for `channel` of `mix`, or `None` if the mix isn't being
+    /// rendered.
+    pub fn channel_mut(&mut self, mix: usize, channel: usize) -> Option<&mut [f32]> {
+        if !self.is_mix_enabled(mix) || channel >= self.channels {
+            return None;
+        }
+        let data = self.raw.output[mix].data[channel];
+        if data.is_null() {
+            return None;
+        }
+        unsafe {
+            Some(std::slice::from_raw_parts_mut(
+                data,
+                AUDIO_OUTPUT_FRAMES as usize,
+            ))
+        }
+    }
+
+    /// All channel buffers of `mix`, empty if the mix isn't being rendered.
+    pub fn mix_mut(&mut self, mix: usize) -> impl Iterator<Item = &mut [f32]> {
+        let channels = if self.is_mix_enabled(mix) {
+            self.channels
+        } else {
+            0
+        };
+        self.raw.output[mix.min(MAX_AUDIO_MIXES as usize - 1)].data[..channels]
+            .iter()
+            .filter(|data| !data.is_null())
+            // each channel is a separate buffer, so the slices don't alias
+            .map(|data| unsafe {
+                std::slice::from_raw_parts_mut(*data, AUDIO_OUTPUT_FRAMES as usize)
+            })
+    }
+
+    /// Add the audio of a child source to every enabled mix, offset by the
+    /// difference between its timestamp and [`timestamp`](Self::timestamp).
+    ///
+    /// Returns `false` if none of the child's audio falls into this output.
+    pub fn add(&mut self, child: &AudioMix<'_>) -> bool {
+        let start = self.timestamp();
+        let frames = self.frames();
+        if child.timestamp() < start {
+            return false;
+        }
+        let offset = frames_between(self.sample_rate, start, child.timestamp());
+        if offset >= frames {
+            return false;
+        }
+
+        for mix in 0..MAX_AUDIO_MIXES as usize {
+            for channel in 0..self.channels.min(child.channels()) {
+                let input = match child.channel(mix, channel) {
+                    Some(input) => input,
+                    None => continue,
+                };
+                if let Some(output) = self.channel_mut(mix, channel) {
+                    for (output, input) in output[offset..].iter_mut().zip(input) {
+                        *output += *input;
+                    }
+                }
+            }
+        }
+        true
+    }
+
+    /// Mix the audio of child sources, the way scenes do. The output
+    /// timestamp is set to that of the earliest child with audio.
+    ///
+    /// Children which are still waiting for audio are skipped. Returns
+    /// `false` if no child had audio, which should be returned from
+    /// [`audio_render`](super::traits::AudioRenderSource::audio_render).
+    pub fn mix_sources<'s, I: IntoIterator<Item = &'s SourceRef>>(&mut self, sources: I) -> bool {
+        let mixes: Vec<AudioMix<'_>> = sources
+            .into_iter()
+            .filter(|source| !source.audio_pending())
+            .map(|source| source.audio_mix())
+            .filter(|mix| mix.timestamp() != 0)
+            .collect();
+
+        let start = match mixes.iter().map(|mix| mix.timestamp()).min() {
+            Some(start) => start,
+            None => return false,
+        };
+        self.set_timestamp(start);
+        for mix in &mixes {
+            self.add(mix);
+        }
+        true
+    }
+}
+
+impl SourceRef {
+    /// Whether the source has no audio ready yet for the current tick.
+    pub fn audio_pending(&self) -> bool {
+        unsafe { obs_source_audio_pending(self.inner) }
+    }
+
+    pub fn audio_active(&self) -> bool {
+        unsafe { obs_source_audio_active(self.inner) }
+    }
+
+    /// Timestamp of the audio the source rendered for the current tick.
+    pub fn audio_timestamp(&self) -> u64 {
+        unsafe { obs_source_get_audio_timestamp(self.inner) }
+    }
+
+    /// Bit mask of the mixes the source outputs to.
+    pub fn audio_mixers(&self) -> u32 {
+        unsafe { obs_source_get_audio_mixers(self.inner) }
+    }
+
+    /// The audio the source rendered for the current tick, for mixing child
+    /// sources in
+    /// [`AudioRenderSource::audio_render`](super::traits::AudioRenderSource::audio_render).
+    ///
+    /// Children must be reported with
+    /// [`EnumActiveContext::enum_child`](super::EnumActiveContext::enum_child)
+    /// for OBS to render their audio before the parent.
+    pub fn audio_mix(&self) -> AudioMix<'_> {
+        let mut raw = obs_source_audio_mix::default();
+        let channels = unsafe {
+            obs_source_get_audio_mix(self.inner, &mut raw);
+            let audio = obs_get_audio();
+            if audio.is_null() {
+                0
+            } else {
+                audio_output_get_channels(audio)
+            }
+        };
+        AudioMix {
+            raw,
+            timestamp: self.audio_timestamp(),
+            channels: channels.min(MAX_AV_PLANES as usize),
+            _marker: PhantomData,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FRAMES: usize = AUDIO_OUTPUT_FRAMES as usize;
+    const MIXES: usize = MAX_AUDIO_MIXES as usize;
+    const START: u64 = 1_000_000_000;
+
+    /// One buffer per channel of every mix, filled with `value`.
+    fn buffers(channels: usize, value: f32) -> Vec<Vec<f32>> {
+        vec![vec![value; FRAMES]; MIXES * channels]
+    }
+
+    fn raw_mix(buffers: &mut [Vec<f32>], channels: usize) -> obs_source_audio_mix {
+        let mut raw = obs_source_audio_mix::default();
+        for (idx, buffer) in buffers.iter_mut().enumerate() {
+            raw.output[idx / channels].data[idx % channels] = buffer.as_mut_ptr();
+        }
+        raw
+    }
+
+    fn child(buffers: &mut [Vec<f32>], channels: usize, timestamp: u64) -> AudioMix<'static> {
+        AudioMix {
+            raw: raw_mix(buffers, channels),
+            timestamp,
+            channels,
+            _marker: PhantomData,
+        }
+    }
+
+    #[test]
+    fn frames_between_timestamps() {
+        assert_eq!(frames_between(48000, START, START), 0);
+        assert_eq!(frames_between(48000, START, START + 1_000_000), 48);
+        assert_eq!(frames_between(44100, START, START + 1_000_000_000), 44100);
+    }
+
+    #[test]
+    fn add_offsets_later_child() {
+        let mut out = buffers(2, 0.);
+        let mut raw = raw_mix(&mut out, 2);
+        let mut timestamp = START;
+        let mut output = AudioMixOutput::new(&mut raw, &mut timestamp, 0b11_1111, 2, 48000);
+
+        let mut input = buffers(2, 0.5);
+        // 1ms later is 48 frames into the output
+        assert!(output.add(&child(&mut input, 2, START + 1_000_000)));
+        assert!(output.add(&child(&mut input, 2, START + 1_000_000)));
+        assert!(output
+            .mix_mut(5)
+            .all(|c| c[..48] == [0.; 48] && c[48..] == [1.; FRAMES - 48]));
+
+        for buffer in &out {
+            assert_eq!(buffer[47], 0.);
+            assert_eq!(buffer[48], 1.);
+            assert_eq!(buffer[FRAMES - 1], 1.);
+        }
+    }
+
+    #[test]
+    fn add_skips_child_outside_output() {
+        let mut out = buffers(2, 0.);
+        let mut raw = raw_mix(&mut out, 2);
+        let mut timestamp = START;
+        let mut output = AudioMixOutput::new(&mut raw, &mut timestamp, 0b11_1111, 2, 48000);
+        let mut input = buffers(2, 0.5);
+
+        // before the output starts
+        assert!(!output.add(&child(&mut input, 2, START - 1)));
+        // exactly `frames` and further in
+        let end = START + (FRAMES as u64 * 1_000_000_000).div_ceil(48000);
+        assert_eq!(frames_between(48000, START, end), FRAMES);
+        assert!(!output.add(&child(&mut input, 2, end)));
+        assert!(!output.add(&child(&mut input, 2, end + 1_000_000)));
+
+        assert!(out.iter().flatten().all(|s| *s == 0.));
+    }
+
+    #[test]
+    fn add_child_with_fewer_channels() {
+        let mut out = buffers(2, 0.);
+        let mut raw = raw_mix(&mut out, 2);
+        let mut timestamp = START;
+        let mut output = AudioMixOutput::new(&mut raw, &mut timestamp, 0b11_1111, 2, 48000);
+
+        let mut input = buffers(1, 0.5);
+        assert!(output.add(&child(&mut input, 1, START)));
+
+        for (idx, buffer) in out.iter().enumerate() {
+            let expected = if idx % 2 == 0 { 0.5 } else { 0. };
+            assert!(buffer.iter().all(|s| *s == expected), "buffer {}", idx);
+        }
+    }
+
+    #[test]
+    fn add_only_enabled_mixes() {
+        let mut out = buffers(2, 0.);
+        let mut raw = raw_mix(&mut out, 2);
+        let mut timestamp = START;
+        // only mixes 0 and 2
+        let mut output = AudioMixOutput::new(&mut raw, &mut timestamp, 0b101, 2, 48000);
+        assert!(output.is_mix_enabled(0));
+        assert!(!output.is_mix_enabled(1));
+        assert!(!output.is_mix_enabled(MIXES));
+        assert!(output.channel_mut(1, 0).is_none());
+        assert!(output.channel_mut(0, 2).is_none());
+
+        let mut input = buffers(2, 0.5);
+        assert!(output.add(&child(&mut input, 2, START)));
+
+        for (idx, buffer) in out.iter().enumerate() {
+            let expected = if matches!(idx / 2, 0 | 2) { 0.5 } else { 0. };
+            assert!(buffer.iter().all(|s| *s == expected), "buffer {}", idx);
+        }
+    }
+
+    #[test]
+    fn mix_mut_out_of_range() {
+        let mut out = buffers(2, 0.);
+        let mut raw = raw_mix(&mut out, 2);
+        let mut timestamp = START;
+        let mut output = AudioMixOutput::new(&mut raw, &mut timestamp, u32::MAX, 2, 48000);
+
+        assert_eq!(output.mix_mut(MIXES - 1).count(), 2);
+        assert_eq!(output.mix_mut(MIXES).count(), 0);
+        assert_eq!(output.mix_mut(usize::MAX).count(), 0);
+        assert!(output.channel_mut(MIXES, 0).is_none());
+    }
+}
@@ -0,0 +1,225 @@
+use std::collections::VecDeque;
+
+use super::traits::FilterAudioSource;
+use crate::media::audio::AudioFrame;
+
+/// Audio processing which works on fixed size blocks, see [`BlockProcessor`].
+pub trait ProcessBlock<const N: usize> {
+    /// Process one block of `N` samples for every channel, in place.
+    fn process_block(&mut self, channels: &mut [[f32; N]]);
+
+    /// Clear any state kept between blocks, called when the
+    /// [`BlockProcessor`] is reset.
+    fn reset(&mut self) {}
+}
+
+/// Adapts a [`ProcessBlock`] to the variable amount of audio OBS passes to
+/// [`FilterAudioSource::filter_audio`].
+///
+/// Incoming audio is buffered per channel until a full block of `N` samples
+/// is available. The output is delayed by `N` samples, starting with silence,
+/// so that every call can be answered in full. The timestamp of the audio is
+/// passed through as is and doesn't account for this delay, see
+/// [`latency_ns`](Self::latency_ns).
+///
+/// ```ignore
+/// impl FilterAudioSource for MyFilter {
+///     fn filter_audio(&mut self, audio: &mut AudioFrame) {
+///         self.processor.filter_audio(audio);
+///     }
+/// }
+/// ```
+pub struct BlockProcessor<P, const N: usize> {
+    processor: P,
+    input: Vec<VecDeque<f32>>,
+    output: Vec<VecDeque<f32>>,
+    blocks: Vec<[f32; N]>,
+}
+
+impl<P: ProcessBlock<N>, const N: usize> BlockProcessor<P, N> {
+    pub fn new(processor: P) -> Self {
+        Self {
+            processor,
+            input: Vec::new(),
+            output: Vec::new(),
+            blocks: Vec::new(),
+        }
+    }
+
+    pub fn processor(&self) -> &P {
+        &self.processor
+    }
+
+    pub fn processor_mut(&mut self) -> &mut P {
+        &mut self.processor
+    }
+
+    pub fn into_inner(self) -> P {
+        self.processor
+    }
+
+    /// Delay added to the audio, in samples.
+    pub fn latency_frames(&self) -> usize {
+        N
+    }
+
+    /// Delay added to the audio in nanoseconds, at `sample_rate`.
+    pub fn latency_ns(&self, sample_rate: usize) -> u64 {
+        N as u64 * 1_000_000_000 / sample_rate.max(1) as u64
+    }
+
+    /// Drop all buffered audio and reset the processor, e.g. after the
+    /// filter was disabled or the channel count changed.
+    ///
+    /// Input which didn't fill a whole block yet is discarded, not processed,
+    /// and the output starts over with `N` samples of silence.
+    pub fn reset(&mut self) {
+        let channels = self.input.len();
+        self.resize(channels);
+        self.processor.reset();
+    }
+
+    fn resize(&mut self, channels: usize) {
+        self.input = vec![VecDeque::with_capacity(N * 2); channels];
+        self.output = vec![VecDeque::from(vec![0.; N]); channels];
+        self.blocks = vec![[0.; N]; channels];
+    }
+}
+
+impl<P: ProcessBlock<N>, const N: usize> FilterAudioSource for BlockProcessor<P, N> {
+    fn filter_audio(&mut self, audio: &mut AudioFrame) {
+        if audio.channels() != self.input.len() {
+            self.resize(audio.channels());
+            self.processor.reset();
+        }
+        if N == 0 || self.input.is_empty() {
+            return;
+        }
+
+        for (input, channel) in self.input.iter_mut().zip(audio.iter_channels()) {
+            input.extend(channel.iter().copied());
+        }
+
+        while self.input[0].len() >= N {
+            for (block, input) in self.blocks.iter_mut().zip(self.input.iter_mut()) {
+                for (sample, input) in block.iter_mut().zip(input.drain(..N)) {
+                    *sample = input;
+                }
+            }
+            self.processor.process_block(&mut self.blocks);
+            for (output, block) in self.output.iter_mut().zip(self.blocks.iter()) {
+                output.extend(block.iter().copied());
+            }
+        }
+
+        // the initial `N` samples of silence mean there is always enough
+        // output buffered for the whole frame
+        for (channel, output) in audio.iter_channels_mut().zip(self.output.iter_mut()) {
+            let frames = channel.len().min(output.len());
+            for (sample, output) in channel.iter_mut().zip(output.drain(..frames)) {
+                *sample = output;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use obs_sys::{obs_audio_data, MAX_AV_PLANES};
+
+    use super::*;
+
+    /// Doubles every sample and counts blocks and resets.
+    #[derive(Default)]
+    struct Double {
+        blocks: usize,
+        resets: usize,
+    }
+
+    impl<const N: usize> ProcessBlock<N> for Double {
+        fn process_block(&mut self, channels: &mut [[f32; N]]) {
+            self.blocks += 1;
+            for sample in channels.iter_mut().flatten() {
+                *sample *= 2.;
+            }
+        }
+
+        fn reset(&mut self) {
+            self.resets += 1;
+        }
+    }
+
+    /// Run `planes` through `processor` in place.
+    fn filter<const N: usize>(processor: &mut BlockProcessor<Double, N>, planes: &mut [Vec<f32>]) {
+        let mut raw = obs_audio_data {
+            data: [std::ptr::null_mut(); MAX_AV_PLANES as usize],
+            frames: planes[0].len() as u32,
+            timestamp: 0,
+        };
+        for (data, plane) in raw.data.iter_mut().zip(planes.iter_mut()) {
+            *data = plane.as_mut_ptr() as *mut u8;
+        }
+        let mut frame = AudioFrame::from_raw_with_channels(&mut raw, planes.len());
+        processor.filter_audio(&mut frame);
+    }
+
+    #[test]
+    fn delays_by_block_size() {
+        const N: usize = 16;
+        let mut processor = BlockProcessor::<_, N>::new(Double::default());
+        let mut position = 0;
+        for frames in [7, 480, 1, 7, 480, 1] {
+            let input: Vec<f32> = (position..position + frames)
+                .map(|i| i as f32 + 1.)
+                .collect();
+            let mut planes = vec![input.clone(), input.iter().map(|s| -s).collect()];
+            filter(&mut processor, &mut planes);
+
+            for (i, (left, right)) in planes[0].iter().zip(&planes[1]).enumerate() {
+                let expected = match position + i {
+                    i if i < N => 0.,
+                    i => (i - N) as f32 * 2. + 2.,
+                };
+                assert_eq!(*left, expected, "sample {}", position + i);
+                assert_eq!(*right, -expected, "sample {}", position + i);
+            }
+            position += frames;
+        }
+        assert_eq!(processor.processor().blocks, position / N);
+        assert_eq!(processor.latency_frames(), N);
+        assert_eq!(processor.latency_ns(48000), 333_333);
+    }
+
+    #[test]
+    fn reset_clears_buffers() {
+        let mut processor = BlockProcessor::<_, 4>::new(Double::default());
+        let mut planes = vec![vec![1.; 6]];
+        filter(&mut processor, &mut planes);
+        assert_eq!(planes[0], [0., 0., 0., 0., 2., 2.]);
+        let resets = processor.processor().resets;
+
+        processor.reset();
+        assert_eq!(processor.processor().resets, resets + 1);
+
+        // the two buffered input and output samples are gone
+        let mut planes = vec![vec![3.; 6]];
+        filter(&mut processor, &mut planes);
+        assert_eq!(planes[0], [0., 0., 0., 0., 6., 6.]);
+    }
+
+    #[test]
+    fn channel_change_resets() {
+        let mut processor = BlockProcessor::<_, 4>::new(Double::default());
+        filter(&mut processor, &mut [vec![1.; 6]]);
+        let resets = processor.processor().resets;
+
+        filter(&mut processor, &mut [vec![1.; 6]]);
+        assert_eq!(processor.processor().resets, resets);
+
+        let mut planes = vec![vec![1.; 6], vec![1.; 6]];
+        filter(&mut processor, &mut planes);
+        assert_eq!(processor.processor().resets, resets + 1);
+        assert_eq!(planes[0], [0., 0., 0., 0., 2., 2.]);
+        assert_eq!(planes[1], [0., 0., 0., 0., 2., 2.]);
+    }
+}
@@ -1,8 +1,9 @@
+use crate::graphics::{GraphicsColorSpace, GraphicsEffectContext};
 use crate::hotkey::{Hotkey, HotkeyCallbacks};
 use crate::media::audio::AudioRef;
 use crate::prelude::DataObj;
 use crate::string::ObsString;
-use obs_sys::obs_get_audio;
+use obs_sys::{gs_get_color_space, obs_get_audio};
 
 pub struct GlobalContext;
 pub struct VideoRenderContext;
@@ -14,6 +15,20 @@ impl GlobalContext {
     }
 }
 
+impl VideoRenderContext {
+    /// Get a context for setting effect parameters and drawing with effect
+    /// techniques while the source is rendering.
+    pub fn effect_context(&mut self) -> GraphicsEffectContext {
+        unsafe { GraphicsEffectContext::new() }
+    }
+
+    /// The color space of the current render target.
+    pub fn color_space(&self) -> GraphicsColorSpace {
+        let raw = unsafe { gs_get_color_space() };
+        GraphicsColorSpace::from_raw(raw).unwrap_or(GraphicsColorSpace::Srgb)
+    }
+}
+
 impl Default for VideoRenderContext {
     fn default() -> Self {
         Self
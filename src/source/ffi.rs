@@ -1,9 +1,10 @@
 use super::context::{CreatableSourceContext, GlobalContext, VideoRenderContext};
-use super::{traits::*, SourceRef};
+use super::{traits::*, AudioMixOutput, SourceRef};
 use super::{EnumActiveContext, EnumAllContext};
-use crate::media::{audio::AudioDataContext, video::VideoDataSourceContext};
+use crate::media::{audio::AudioFrame, video::VideoDataSourceContext};
 use crate::{
     data::DataObj,
+    graphics::GraphicsColorSpace,
     hotkey::{Hotkey, HotkeyCallbacks},
     wrapper::PtrWrapper,
 };
@@ -15,7 +16,7 @@ use std::mem::forget;
 use std::os::raw::c_char;
 
 use obs_sys::{
-    gs_effect_t, obs_audio_data, obs_button_type, obs_data_t, obs_hotkey_id,
+    gs_color_space, gs_effect_t, obs_audio_data, obs_button_type, obs_data_t, obs_hotkey_id,
     obs_hotkey_register_source, obs_hotkey_t, obs_key_event, obs_media_state, obs_mouse_event,
     obs_properties, obs_source_audio_mix, obs_source_enum_proc_t, obs_source_frame, obs_source_t,
     size_t,
@@ -23,6 +24,7 @@ use obs_sys::{
 
 struct DataWrapper<D> {
     data: D,
+    source: *mut obs_source_t,
     #[allow(clippy::type_complexity)]
     hotkey_callbacks: HashMap<obs_hotkey_id, Box<dyn FnMut(&mut Hotkey, &mut D)>>,
 }
@@ -48,10 +50,11 @@ impl<D> DataWrapper<D> {
     }
 }
 
-impl<D> From<D> for DataWrapper<D> {
-    fn from(data: D) -> Self {
+impl<D> DataWrapper<D> {
+    fn new(data: D, source: *mut obs_source_t) -> Self {
         Self {
             data,
+            source,
             hotkey_callbacks: HashMap::new(),
         }
     }
@@ -94,7 +97,7 @@ pub unsafe extern "C" fn create<D: Sourceable>(
 
     let data = D::create(&mut context, source_context);
 
-    let wrapper = DataWrapper::from(data);
+    let wrapper = DataWrapper::new(data, source);
     forget(context.settings);
     let callbacks = context.hotkey_callbacks;
 
@@ -133,17 +136,22 @@ pub unsafe extern "C" fn video_render<D: VideoRenderSource>(
 
 pub unsafe extern "C" fn audio_render<D: AudioRenderSource>(
     data: *mut std::os::raw::c_void,
-    _ts_out: *mut u64,
-    _audio_output: *mut obs_source_audio_mix,
-    _mixers: u32,
-    _channels: size_t,
-    _sample_rate: size_t,
+    ts_out: *mut u64,
+    audio_output: *mut obs_source_audio_mix,
+    mixers: u32,
+    channels: size_t,
+    sample_rate: size_t,
 ) -> bool {
     let wrapper: &mut DataWrapper<D> = &mut *(data as *mut DataWrapper<D>);
     let mut global = GlobalContext;
-    D::audio_render(&mut wrapper.data, &mut global);
-    // TODO: understand what this bool is
-    true
+    let mut output = AudioMixOutput::new(
+        &mut *audio_output,
+        &mut *ts_out,
+        mixers,
+        channels,
+        sample_rate,
+    );
+    D::audio_render(&mut wrapper.data, &mut global, &mut output)
 }
 
 pub unsafe extern "C" fn get_properties<D: GetPropertiesSource>(
@@ -156,21 +164,21 @@ pub unsafe extern "C" fn get_properties<D: GetPropertiesSource>(
 
 pub unsafe extern "C" fn enum_active_sources<D: EnumActiveSource>(
     data: *mut std::os::raw::c_void,
-    _enum_callback: obs_source_enum_proc_t,
-    _param: *mut std::os::raw::c_void,
+    enum_callback: obs_source_enum_proc_t,
+    param: *mut std::os::raw::c_void,
 ) {
     let wrapper: &mut DataWrapper<D> = &mut *(data as *mut DataWrapper<D>);
-    let context = EnumActiveContext {};
+    let context = EnumActiveContext::new(wrapper.source, enum_callback, param);
     D::enum_active_sources(&mut wrapper.data, &context);
 }
 
 pub unsafe extern "C" fn enum_all_sources<D: EnumAllSource>(
     data: *mut std::os::raw::c_void,
-    _enum_callback: obs_source_enum_proc_t,
-    _param: *mut std::os::raw::c_void,
+    enum_callback: obs_source_enum_proc_t,
+    param: *mut std::os::raw::c_void,
 ) {
     let wrapper: &mut DataWrapper<D> = &mut *(data as *mut DataWrapper<D>);
-    let context = EnumAllContext {};
+    let context = EnumAllContext::new(wrapper.source, enum_callback, param);
     D::enum_all_sources(&mut wrapper.data, &context);
 }
 
@@ -179,6 +187,23 @@ impl_simple_fn!(
     transition_stop => TransitionStopSource
 );
 
+pub unsafe extern "C" fn video_get_color_space<D: GetColorSpaceSource>(
+    data: *mut std::os::raw::c_void,
+    count: usize,
+    preferred_spaces: *const gs_color_space,
+) -> gs_color_space {
+    let wrapper: &mut DataWrapper<D> = &mut *(data as *mut DataWrapper<D>);
+    let preferred: Vec<GraphicsColorSpace> = if preferred_spaces.is_null() {
+        Vec::new()
+    } else {
+        std::slice::from_raw_parts(preferred_spaces, count)
+            .iter()
+            .filter_map(|&space| GraphicsColorSpace::from_raw(space).ok())
+            .collect()
+    };
+    D::video_get_color_space(&mut wrapper.data, &preferred).as_raw()
+}
+
 pub unsafe extern "C" fn video_tick<D: VideoTickSource>(
     data: *mut std::os::raw::c_void,
     seconds: f32,
@@ -191,7 +216,7 @@ pub unsafe extern "C" fn filter_audio<D: FilterAudioSource>(
     data: *mut std::os::raw::c_void,
     audio: *mut obs_audio_data,
 ) -> *mut obs_audio_data {
-    let mut context = AudioDataContext::from_raw(audio);
+    let mut context = AudioFrame::from_raw(audio);
     let wrapper: &mut DataWrapper<D> = &mut *(data as *mut DataWrapper<D>);
     D::filter_audio(&mut wrapper.data, &mut context);
     audio
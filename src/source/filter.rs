@@ -0,0 +1,215 @@
+use obs_sys::{
+    obs_filter_get_parent, obs_filter_get_target, obs_source_default_render,
+    obs_source_get_base_height, obs_source_get_base_width, obs_source_get_output_flags,
+    obs_source_video_render, OBS_SOURCE_ASYNC, OBS_SOURCE_CUSTOM_DRAW,
+};
+
+use super::{SourceRef, VideoRenderContext};
+use crate::graphics::{
+    BlendStateGuard, GraphicsBlendType, GraphicsColorFormat, GraphicsEffectContext,
+    GraphicsTexRender, GraphicsTexture, GraphicsTextureRef, GraphicsZStencilFormat, Vec4,
+};
+
+/// Helper for filters which need more control over drawing than
+/// [`SourceRef::process_filter`] gives, e.g. multi-pass effects.
+///
+/// Each frame the filter target is captured into a texture, which is then
+/// passed through any number of passes, each rendering into an intermediate
+/// texture, before the result is drawn to the output.
+///
+/// ```ignore
+/// fn video_render(&mut self, _context: &mut GlobalContext, render: &mut VideoRenderContext) {
+///     let effect = &mut self.effect;
+///     self.renderer.render(&mut self.source, render, |passes| {
+///         let (cx, cy) = passes.size();
+///         passes.pass(|ctx, input| {
+///             // draw `input` with the first technique
+///         });
+///         passes.output(|ctx, input| {
+///             // draw `input` with the final technique
+///         });
+///     });
+/// }
+/// ```
+pub struct FilterRenderer {
+    input: GraphicsTexRender,
+    ping: GraphicsTexRender,
+    pong: GraphicsTexRender,
+}
+
+impl FilterRenderer {
+    /// Create a renderer whose intermediate textures use `format`.
+    pub fn new(format: GraphicsColorFormat) -> Self {
+        Self {
+            input: GraphicsTexRender::new(format, GraphicsZStencilFormat::None),
+            ping: GraphicsTexRender::new(format, GraphicsZStencilFormat::None),
+            pong: GraphicsTexRender::new(format, GraphicsZStencilFormat::None),
+        }
+    }
+
+    /// Capture the target of `filter` and run `func` with it.
+    ///
+    /// Calls [`SourceRef::skip_video_filter`] instead if the filter is
+    /// disabled, has no target, the target has no size, or capturing it
+    /// failed.
+    pub fn render<F: FnOnce(&mut FilterPasses<'_>)>(
+        &mut self,
+        filter: &mut SourceRef,
+        render: &mut VideoRenderContext,
+        func: F,
+    ) {
+        if !filter.enabled() || !self.capture(filter, render) {
+            filter.skip_video_filter();
+            return;
+        }
+
+        let (cx, cy) = unsafe {
+            let target = obs_filter_get_target(filter.inner);
+            (
+                obs_source_get_base_width(target),
+                obs_source_get_base_height(target),
+            )
+        };
+
+        let mut passes = FilterPasses {
+            renderer: self,
+            render,
+            current: Current::Input,
+            cx,
+            cy,
+        };
+        func(&mut passes);
+    }
+
+    fn capture(&mut self, filter: &mut SourceRef, render: &mut VideoRenderContext) -> bool {
+        unsafe {
+            let target = obs_filter_get_target(filter.inner);
+            let parent = obs_filter_get_parent(filter.inner);
+            if target.is_null() || parent.is_null() {
+                return false;
+            }
+
+            let cx = obs_source_get_base_width(target);
+            let cy = obs_source_get_base_height(target);
+            if cx == 0 || cy == 0 {
+                return false;
+            }
+
+            let flags = obs_source_get_output_flags(target);
+            let custom_draw = flags & OBS_SOURCE_CUSTOM_DRAW != 0;
+            let is_async = flags & OBS_SOURCE_ASYNC != 0;
+
+            self.input.reset();
+            let guard = match self.input.begin(render, cx, cy) {
+                Some(guard) => guard,
+                None => return false,
+            };
+            guard.clear(&Vec4::default());
+
+            let context = render.effect_context();
+            let mut blend = BlendStateGuard::push(&context);
+            blend.function(GraphicsBlendType::One, GraphicsBlendType::Zero);
+
+            if target == parent && !custom_draw && !is_async {
+                obs_source_default_render(target);
+            } else {
+                obs_source_video_render(target);
+            }
+        }
+        true
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Current {
+    Input,
+    Ping,
+    Pong,
+}
+
+/// The passes of a [`FilterRenderer`] for a single frame.
+pub struct FilterPasses<'a> {
+    renderer: &'a mut FilterRenderer,
+    render: &'a mut VideoRenderContext,
+    current: Current,
+    cx: u32,
+    cy: u32,
+}
+
+impl FilterPasses<'_> {
+    /// Size of the captured target.
+    pub fn size(&self) -> (u32, u32) {
+        (self.cx, self.cy)
+    }
+
+    /// The captured filter target, before any passes ran.
+    pub fn input(&self) -> Option<GraphicsTextureRef<'_>> {
+        self.renderer.input.texture()
+    }
+
+    /// The output of the last pass, or the captured target if no passes ran
+    /// yet.
+    pub fn current(&self) -> Option<GraphicsTextureRef<'_>> {
+        match self.current {
+            Current::Input => self.renderer.input.texture(),
+            Current::Ping => self.renderer.ping.texture(),
+            Current::Pong => self.renderer.pong.texture(),
+        }
+    }
+
+    /// Run a pass, drawing into an intermediate texture which becomes the
+    /// input of the next pass. `func` is given the output of the previous pass
+    /// and should draw it covering `(0, 0)` to [`size`](Self::size).
+    ///
+    /// Returns `false` if the intermediate texture could not be rendered to.
+    pub fn pass<F: FnOnce(&mut GraphicsEffectContext, &GraphicsTexture)>(
+        &mut self,
+        func: F,
+    ) -> bool {
+        let renderer = &mut *self.renderer;
+        let (source, dest, next) = match self.current {
+            Current::Input => (&renderer.input, &mut renderer.ping, Current::Ping),
+            Current::Ping => (&renderer.ping, &mut renderer.pong, Current::Pong),
+            Current::Pong => (&renderer.pong, &mut renderer.ping, Current::Ping),
+        };
+        let texture = match source.texture() {
+            Some(texture) => texture,
+            None => return false,
+        };
+
+        let state = self.render.effect_context();
+        let mut context = self.render.effect_context();
+        {
+            dest.reset();
+            let guard = match dest.begin(self.render, self.cx, self.cy) {
+                Some(guard) => guard,
+                None => return false,
+            };
+            guard.clear(&Vec4::default());
+
+            let mut blend = BlendStateGuard::push(&state);
+            blend.function(GraphicsBlendType::One, GraphicsBlendType::Zero);
+            func(&mut context, &texture);
+        }
+        self.current = next;
+        true
+    }
+
+    /// Draw the final output of the filter. `func` is given the output of the
+    /// last pass and should draw it to the current render target, usually
+    /// with [`draw_sprite`](crate::graphics::draw_sprite).
+    pub fn output<F: FnOnce(&mut GraphicsEffectContext, &GraphicsTexture)>(&mut self, func: F) {
+        let renderer = &*self.renderer;
+        let texture = match self.current {
+            Current::Input => renderer.input.texture(),
+            Current::Ping => renderer.ping.texture(),
+            Current::Pong => renderer.pong.texture(),
+        };
+        let texture = match texture {
+            Some(texture) => texture,
+            None => return,
+        };
+        let mut context = self.render.effect_context();
+        func(&mut context, &texture);
+    }
+}
@@ -1,7 +1,12 @@
 use paste::item;
+use std::os::raw::c_void;
 
+pub mod audio_capture;
+pub mod audio_mix;
+pub mod block;
 pub mod context;
 mod ffi;
+pub mod filter;
 pub mod scene;
 pub mod traits;
 
@@ -11,7 +16,11 @@ use crate::{
     Result,
 };
 
+pub use audio_capture::*;
+pub use audio_mix::*;
+pub use block::*;
 pub use context::*;
+pub use filter::*;
 pub use traits::*;
 
 use obs_sys::{
@@ -25,24 +34,26 @@ use obs_sys::{
     obs_icon_type_OBS_ICON_TYPE_WINDOW_CAPTURE, obs_mouse_button_type,
     obs_mouse_button_type_MOUSE_LEFT, obs_mouse_button_type_MOUSE_MIDDLE,
     obs_mouse_button_type_MOUSE_RIGHT, obs_source_active, obs_source_enabled,
-    obs_source_get_base_height, obs_source_get_base_width, obs_source_get_height,
-    obs_source_get_id, obs_source_get_name, obs_source_get_ref, obs_source_get_type,
-    obs_source_get_width, obs_source_info, obs_source_media_ended, obs_source_media_get_duration,
-    obs_source_media_get_state, obs_source_media_get_time, obs_source_media_next,
-    obs_source_media_play_pause, obs_source_media_previous, obs_source_media_restart,
-    obs_source_media_set_time, obs_source_media_started, obs_source_media_stop,
-    obs_source_process_filter_begin, obs_source_process_filter_end,
+    obs_source_enum_proc_t, obs_source_get_base_height, obs_source_get_base_width,
+    obs_source_get_color_space, obs_source_get_height, obs_source_get_id, obs_source_get_name,
+    obs_source_get_ref, obs_source_get_type, obs_source_get_width, obs_source_info,
+    obs_source_media_ended, obs_source_media_get_duration, obs_source_media_get_state,
+    obs_source_media_get_time, obs_source_media_next, obs_source_media_play_pause,
+    obs_source_media_previous, obs_source_media_restart, obs_source_media_set_time,
+    obs_source_media_started, obs_source_media_stop, obs_source_process_filter_begin,
+    obs_source_process_filter_begin_with_color_space, obs_source_process_filter_end,
     obs_source_process_filter_tech_end, obs_source_release, obs_source_set_enabled,
     obs_source_set_name, obs_source_showing, obs_source_skip_video_filter, obs_source_t,
     obs_source_type, obs_source_type_OBS_SOURCE_TYPE_FILTER, obs_source_type_OBS_SOURCE_TYPE_INPUT,
     obs_source_type_OBS_SOURCE_TYPE_SCENE, obs_source_type_OBS_SOURCE_TYPE_TRANSITION,
-    obs_source_update, OBS_SOURCE_AUDIO, OBS_SOURCE_CONTROLLABLE_MEDIA, OBS_SOURCE_INTERACTION,
-    OBS_SOURCE_VIDEO,
+    obs_source_update, OBS_SOURCE_AUDIO, OBS_SOURCE_COMPOSITE, OBS_SOURCE_CONTROLLABLE_MEDIA,
+    OBS_SOURCE_INTERACTION, OBS_SOURCE_VIDEO,
 };
 
 use super::{
     graphics::{
-        GraphicsAllowDirectRendering, GraphicsColorFormat, GraphicsEffect, GraphicsEffectContext,
+        GraphicsAllowDirectRendering, GraphicsColorFormat, GraphicsColorSpace, GraphicsEffect,
+        GraphicsEffectContext,
     },
     string::ObsString,
 };
@@ -186,6 +197,15 @@ impl SourceRef {
         unsafe { obs_source_get_height(self.inner) }
     }
 
+    /// The color space the source will render in, given the spaces the
+    /// caller prefers (most preferred first).
+    pub fn color_space(&self, preferred_spaces: &[GraphicsColorSpace]) -> GraphicsColorSpace {
+        let preferred: Vec<_> = preferred_spaces.iter().map(|s| s.as_raw()).collect();
+        let raw =
+            unsafe { obs_source_get_color_space(self.inner, preferred.len(), preferred.as_ptr()) };
+        GraphicsColorSpace::from_raw(raw).unwrap_or(GraphicsColorSpace::Srgb)
+    }
+
     pub fn media_play_pause(&mut self, pause: bool) {
         unsafe {
             obs_source_media_play_pause(self.inner, pause);
@@ -279,6 +299,42 @@ impl SourceRef {
         }
     }
 
+    /// Like [`process_filter`](Self::process_filter), but renders the filter
+    /// in the given color space so it works on HDR canvases.
+    ///
+    /// `space` is usually picked with [`color_space`](Self::color_space) on
+    /// the filter target, and `format` with [`GraphicsColorSpace::format`].
+    ///
+    /// See [OBS documentation](https://obsproject.com/docs/reference-sources.html#c.obs_source_process_filter_begin_with_color_space)
+    #[allow(clippy::too_many_arguments)]
+    pub fn process_filter_begin_with_color_space<
+        F: FnOnce(&mut GraphicsEffectContext, &mut GraphicsEffect),
+    >(
+        &mut self,
+        _render: &mut VideoRenderContext,
+        effect: &mut GraphicsEffect,
+        (cx, cy): (u32, u32),
+        format: GraphicsColorFormat,
+        space: GraphicsColorSpace,
+        direct: GraphicsAllowDirectRendering,
+        func: F,
+    ) {
+        unsafe {
+            if let Ok(SourceType::Filter) = SourceType::from_raw(obs_source_get_type(self.inner)) {
+                if obs_source_process_filter_begin_with_color_space(
+                    self.inner,
+                    format.as_raw(),
+                    space.as_raw(),
+                    direct.as_raw(),
+                ) {
+                    let mut context = GraphicsEffectContext::new();
+                    func(&mut context, effect);
+                    obs_source_process_filter_end(self.inner, effect.as_ptr(), cx, cy);
+                }
+            }
+        }
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub fn process_filter_tech<F: FnOnce(&mut GraphicsEffectContext, &mut GraphicsEffect)>(
         &mut self,
@@ -315,9 +371,73 @@ impl SourceRef {
     }
 }
 
-pub struct EnumActiveContext {}
+/// The callback libobs passes to `enum_active_sources` and
+/// `enum_all_sources`.
+struct EnumCallback {
+    parent: *mut obs_source_t,
+    callback: obs_source_enum_proc_t,
+    param: *mut c_void,
+}
 
-pub struct EnumAllContext {}
+impl EnumCallback {
+    fn call(&self, child: &SourceRef) {
+        if let Some(callback) = self.callback {
+            unsafe { callback(self.parent, child.inner, self.param) }
+        }
+    }
+}
+
+/// Passed to [`EnumActiveSource::enum_active_sources`], report each child
+/// source that is currently shown with [`enum_child`](Self::enum_child).
+pub struct EnumActiveContext {
+    callback: EnumCallback,
+}
+
+impl EnumActiveContext {
+    pub(crate) fn new(
+        parent: *mut obs_source_t,
+        callback: obs_source_enum_proc_t,
+        param: *mut c_void,
+    ) -> Self {
+        Self {
+            callback: EnumCallback {
+                parent,
+                callback,
+                param,
+            },
+        }
+    }
+
+    pub fn enum_child(&self, child: &SourceRef) {
+        self.callback.call(child);
+    }
+}
+
+/// Passed to [`EnumAllSource::enum_all_sources`], report every child source
+/// with [`enum_child`](Self::enum_child), whether it is shown or not.
+pub struct EnumAllContext {
+    callback: EnumCallback,
+}
+
+impl EnumAllContext {
+    pub(crate) fn new(
+        parent: *mut obs_source_t,
+        callback: obs_source_enum_proc_t,
+        param: *mut c_void,
+    ) -> Self {
+        Self {
+            callback: EnumCallback {
+                parent,
+                callback,
+                param,
+            },
+        }
+    }
+
+    pub fn enum_child(&self, child: &SourceRef) {
+        self.callback.call(child);
+    }
+}
 
 pub struct SourceInfo {
     info: Box<obs_source_info>,
@@ -387,6 +507,11 @@ impl<D: Sourceable> SourceInfoBuilder<D> {
             self.info.output_flags |= OBS_SOURCE_AUDIO;
         }
 
+        // sources rendering audio themselves mix the audio of their children
+        if self.info.audio_render.is_some() {
+            self.info.output_flags |= OBS_SOURCE_COMPOSITE;
+        }
+
         if self.info.media_get_state.is_some() || self.info.media_play_pause.is_some() {
             self.info.output_flags |= OBS_SOURCE_CONTROLLABLE_MEDIA;
         }
@@ -432,6 +557,7 @@ impl_source_builder! {
     deactivate => DeactivateSource
     update => UpdateSource
     video_render => VideoRenderSource
+    video_get_color_space => GetColorSpaceSource
     audio_render => AudioRenderSource
     get_properties => GetPropertiesSource
     enum_active_sources => EnumActiveSource
@@ -1,10 +1,11 @@
 use obs_sys::{obs_key_event, obs_mouse_event};
 
 use super::context::{CreatableSourceContext, GlobalContext, VideoRenderContext};
-use super::{EnumActiveContext, EnumAllContext, SourceRef, SourceType};
+use super::{AudioMixOutput, EnumActiveContext, EnumAllContext, SourceRef, SourceType};
 use crate::data::DataObj;
+use crate::graphics::GraphicsColorSpace;
 use crate::media::state::MediaState;
-use crate::media::{audio::AudioDataContext, video::VideoDataSourceContext};
+use crate::media::{audio::AudioFrame, video::VideoDataSourceContext};
 use crate::properties::Properties;
 use crate::string::ObsString;
 
@@ -67,8 +68,20 @@ pub trait VideoRenderSource: Sized {
     fn video_render(&mut self, context: &mut GlobalContext, render: &mut VideoRenderContext);
 }
 
+pub trait GetColorSpaceSource: Sized {
+    /// Pick the color space the source renders in, given the spaces preferred
+    /// by the canvas it is being drawn to (most preferred first).
+    fn video_get_color_space(
+        &mut self,
+        preferred_spaces: &[GraphicsColorSpace],
+    ) -> GraphicsColorSpace;
+}
+
 pub trait AudioRenderSource: Sized {
-    fn audio_render(&mut self, context: &mut GlobalContext);
+    /// Write the source's audio into `output`, usually by mixing child
+    /// sources with [`AudioMixOutput::mix_sources`]. Return `false` if there
+    /// is no audio this time.
+    fn audio_render(&mut self, context: &mut GlobalContext, output: &mut AudioMixOutput) -> bool;
 }
 
 pub trait GetPropertiesSource: Sized {
@@ -93,7 +106,7 @@ simple_trait!(
 );
 
 pub trait FilterAudioSource: Sized {
-    fn filter_audio(&mut self, audio: &mut AudioDataContext);
+    fn filter_audio(&mut self, audio: &mut AudioFrame);
 }
 
 pub trait FilterVideoSource: Sized {